@@ -0,0 +1,54 @@
+/*
+ * C interface to smorse.
+ *
+ * Every string returned by a smorse_ function is owned by the caller and must
+ * be released with smorse_string_free. Every search returned by
+ * smorse_smalpha_all must be released with smorse_smalpha_free.
+ */
+#ifndef SMORSE_H
+#define SMORSE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* An in-progress search for every permutation of the alphabet which produces a code. */
+typedef struct SmorseSmalphaIter SmorseSmalphaIter;
+
+/* Encode text as squashed morse. NULL if text is NULL or not UTF-8. */
+char *smorse_encode(const char *text);
+
+/* Encode text as spaced morse, which can be decoded. NULL if text is NULL or not UTF-8. */
+char *smorse_encode_spaced(const char *text);
+
+/* Decode spaced morse. NULL if code is NULL or not valid spaced morse. */
+char *smorse_decode(const char *code);
+
+/* The first permutation of the alphabet whose squashed code is code, or NULL. */
+char *smorse_smalpha(const char *code);
+
+/* Start searching for every permutation of the alphabet whose squashed code is code. */
+SmorseSmalphaIter *smorse_smalpha_all(const char *code);
+
+/* The next solution from iter, or NULL once there are no more. */
+char *smorse_smalpha_next(SmorseSmalphaIter *iter);
+
+/* Release a search. NULL is ignored. */
+void smorse_smalpha_free(SmorseSmalphaIter *iter);
+
+/* Release a string returned by any smorse_ function. NULL is ignored. */
+void smorse_string_free(char *s);
+
+/* Allocate and release memory inside the library, for WebAssembly hosts.
+ * Releasing NULL is ignored. */
+uint8_t *smorse_alloc(size_t len);
+void smorse_dealloc(uint8_t *ptr, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SMORSE_H */
@@ -0,0 +1,517 @@
+use crate::encoding::{Sequence, LETTERS};
+use crate::permutation::Permutation;
+use crate::search::{Monitor, SearchOptions, SearchOutcome, SearchStats, SearchStatus};
+use std::error::Error;
+use std::fmt;
+
+/// The length of the squashed code of any permutation of the alphabet.
+const ALPHABET_CODE_LEN: usize = 82;
+
+/// Why a code cannot be produced by any permutation of the alphabet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeError {
+    /// every such code has exactly `ALPHABET_CODE_LEN` symbols
+    Length(usize),
+    /// only dots and dashes are allowed
+    Symbol(char),
+    /// every such code has the same number of dots and dashes as the alphabet
+    Balance { dots: usize, dashes: usize },
+}
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodeError::Length(len) => {
+                write!(f, "expected {} symbols, found {}", ALPHABET_CODE_LEN, len)
+            }
+            CodeError::Symbol(c) => write!(f, "{:?} is neither a dot nor a dash", c),
+            CodeError::Balance { dots, dashes } => write!(
+                f,
+                "expected {} dots and {} dashes, found {} and {}",
+                ALPHABET_DOTS,
+                ALPHABET_CODE_LEN - ALPHABET_DOTS,
+                dots,
+                dashes
+            ),
+        }
+    }
+}
+
+impl Error for CodeError {}
+
+/// The number of dots in the squashed code of any permutation of the alphabet.
+const ALPHABET_DOTS: usize = 44;
+
+/// Check that `code` could be produced by some permutation of the alphabet,
+/// without searching for one.
+pub fn check_alphabet_code(code: &str) -> Result<(), CodeError> {
+    let len = code.chars().count();
+    if len != ALPHABET_CODE_LEN {
+        return Err(CodeError::Length(len));
+    }
+    if let Some(c) = code.chars().find(|&c| c != '.' && c != '-') {
+        return Err(CodeError::Symbol(c));
+    }
+    let dots = code.chars().filter(|&c| c == '.').count();
+    if dots != ALPHABET_DOTS {
+        return Err(CodeError::Balance {
+            dots,
+            dashes: len - dots,
+        });
+    }
+    Ok(())
+}
+
+/// A squashed code which might be produced by some permutation of the alphabet.
+///
+/// `starts[i]` has bit `c` set when letter `c` matches the code at offset `i`,
+/// so each step of the search only considers letters which can actually fit.
+struct PackedCode {
+    starts: [u32; ALPHABET_CODE_LEN],
+}
+
+impl PackedCode {
+    /// Returns `None` for any input which cannot possibly encode an alphabet.
+    fn new(code: &str) -> Option<PackedCode> {
+        if code.len() != ALPHABET_CODE_LEN {
+            return None;
+        }
+        let seq = Sequence::from_code(code)?;
+        let mut starts = [0; ALPHABET_CODE_LEN];
+        for (offset, start) in starts.iter_mut().enumerate() {
+            for (chb, letter) in LETTERS.iter().enumerate() {
+                if seq.matches_at(offset, letter) {
+                    *start |= 1 << chb;
+                }
+            }
+        }
+        Some(PackedCode { starts })
+    }
+}
+
+fn alpha_search(
+    code: &PackedCode,
+    offset: usize,
+    alphabet: &mut u32,
+    prefix: &mut Vec<u8>,
+    monitor: &mut Monitor,
+) -> bool {
+    if !monitor.step() {
+        return false;
+    }
+    if offset == ALPHABET_CODE_LEN || *alphabet == 0 {
+        return offset == ALPHABET_CODE_LEN && *alphabet == 0;
+    }
+    let mut candidates = code.starts[offset] & *alphabet;
+    while candidates != 0 {
+        let chb = candidates.trailing_zeros() as u8;
+        candidates &= candidates - 1;
+        *alphabet &= !(1 << chb);
+        prefix.push(chb);
+        monitor.choose(chb);
+        if alpha_search(
+            code,
+            offset + LETTERS[chb as usize].len(),
+            alphabet,
+            prefix,
+            monitor,
+        ) {
+            monitor.succeed(chb);
+            return true;
+        }
+        monitor.backtrack(prefix.len() - 1);
+        prefix.pop();
+        *alphabet |= 1 << chb;
+    }
+    false
+}
+
+pub fn smalpha(code: &str) -> Option<Permutation> {
+    smalpha_with(code, &SearchOptions::default()).found
+}
+
+/// Like `smalpha`, but gives up when the limits in `options` are reached,
+/// and gathers statistics if they are requested.
+pub fn smalpha_with(code: &str, options: &SearchOptions) -> SearchOutcome<Option<Permutation>> {
+    let mut monitor = Monitor::new(options.clone());
+    let mut alphabet = 0x03ff_ffff; // 26 low bits set
+    let mut prefix = Vec::with_capacity(26);
+    monitor.start();
+    let found = match PackedCode::new(code) {
+        Some(code) => alpha_search(&code, 0, &mut alphabet, &mut prefix, &mut monitor),
+        None => false,
+    };
+    monitor.stop(found);
+    SearchOutcome {
+        found: if found {
+            let mut perm = [0; 26];
+            perm.copy_from_slice(&prefix);
+            Some(Permutation::from_indices(perm))
+        } else {
+            None
+        },
+        status: monitor.stopped().unwrap_or(SearchStatus::Finished),
+        stats: monitor.stats(),
+    }
+}
+
+/// Iterator over every permutation of the alphabet which produces a given code.
+///
+/// If the search is stopped early by its `SearchOptions`, iteration ends
+/// and `status` reports why. If statistics were requested, `stats` reports them.
+pub struct AlphaSearch {
+    code: Option<PackedCode>,
+    alphabet: u32,
+    prefix: [u8; 26],
+    monitor: Monitor,
+    status: Option<SearchStatus>,
+}
+
+impl AlphaSearch {
+    fn new(code: &str, options: &SearchOptions) -> AlphaSearch {
+        AlphaSearch {
+            code: PackedCode::new(code),
+            alphabet: 0x03ff_ffff, // 26 low bits set
+            prefix: [0; 26],
+            monitor: Monitor::new(options.clone()),
+            status: None,
+        }
+    }
+
+    /// Why the search stopped, or `None` if it may still produce results.
+    pub fn status(&self) -> Option<SearchStatus> {
+        match self.code {
+            None => Some(self.status.unwrap_or(SearchStatus::Finished)),
+            Some(_) => None,
+        }
+    }
+
+    /// Statistics about the search so far, if they were requested.
+    pub fn stats(&self) -> Option<SearchStats> {
+        self.monitor.stats()
+    }
+
+    fn add_alpha(&mut self, idx: u8) {
+        self.alphabet |= 1 << idx;
+    }
+
+    fn remove_alpha(&mut self, idx: u8) {
+        self.alphabet &= !(1 << idx);
+    }
+
+    fn next_inner(&mut self, code: &PackedCode, offset: usize, idx: usize) -> bool {
+        if !self.monitor.step() {
+            return false;
+        }
+        if offset == ALPHABET_CODE_LEN || self.alphabet == 0 {
+            return offset == ALPHABET_CODE_LEN && self.alphabet == 0;
+        }
+
+        // only letters at or after the resume point which fit here and are still unused
+        let resume = !((1_u32 << self.prefix[idx]) - 1);
+        let mut candidates = code.starts[offset] & self.alphabet & resume;
+        while candidates != 0 {
+            let chb = candidates.trailing_zeros() as u8;
+            candidates &= candidates - 1;
+            self.prefix[idx] = chb;
+            self.remove_alpha(chb);
+            self.monitor.choose(chb);
+            let ok = self.next_inner(code, offset + LETTERS[chb as usize].len(), idx + 1);
+            if !ok {
+                self.monitor.backtrack(idx);
+                // a stopped search can fail even with every letter placed
+                if idx + 1 < 26 {
+                    self.prefix[idx + 1] = 0;
+                }
+            }
+            self.add_alpha(chb);
+            if ok {
+                self.monitor.succeed(chb);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Iterator for AlphaSearch {
+    type Item = Permutation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // once the search is exhausted, the code is dropped and we stay exhausted
+        let code = self.code.take()?;
+        self.monitor.start();
+        let found = self.next_inner(&code, 0, 0);
+        self.monitor.stop(found);
+        if !found {
+            self.status = self.monitor.stopped();
+            return None;
+        }
+        self.code = Some(code);
+        let result = Some(Permutation::from_indices(self.prefix));
+
+        // we now have to clean up the internal state: if we were to call self.next_inner
+        // again right away, we'd immediately generate the same result, because
+        // we'd just descend right down the same path as before.
+        for idx in (0..26).rev() {
+            if self.prefix[idx] == 25 {
+                // if the last letter is a z, we zeroize it and continue on
+                self.add_alpha(25);
+                self.prefix[idx] = 0;
+            } else {
+                // otherwise, we increment it and stop. This ensures that the normal
+                // recursive pattern will keep generating new combinations for us.
+                self.add_alpha(self.prefix[idx]);
+                self.prefix[idx] += 1;
+                break;
+            }
+        }
+
+        result
+    }
+}
+
+pub fn smalpha_all(code: &str) -> impl Iterator<Item = Permutation> {
+    AlphaSearch::new(code, &SearchOptions::default())
+}
+
+/// Like `smalpha_all`, but gives up when the limits in `options` are reached.
+///
+/// Any results produced before that point are still valid.
+pub fn smalpha_all_with(code: &str, options: &SearchOptions) -> AlphaSearch {
+    AlphaSearch::new(code, options)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::search::CancelToken;
+    use crate::smorse;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_alphabet_code_len() {
+        assert_eq!(
+            crate::MORSE.iter().map(|sym| sym.len()).sum::<usize>(),
+            ALPHABET_CODE_LEN
+        );
+    }
+
+    #[test]
+    fn test_check_alphabet_code() {
+        assert_eq!(
+            check_alphabet_code(&smorse("abcdefghijklmnopqrstuvwxyz")),
+            Ok(())
+        );
+        assert_eq!(check_alphabet_code("-.-"), Err(CodeError::Length(3)));
+        assert_eq!(
+            check_alphabet_code(&"x".repeat(ALPHABET_CODE_LEN)),
+            Err(CodeError::Symbol('x'))
+        );
+        assert_eq!(
+            check_alphabet_code(&".".repeat(ALPHABET_CODE_LEN)),
+            Err(CodeError::Balance {
+                dots: ALPHABET_CODE_LEN,
+                dashes: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_smalpha_bad_input() {
+        assert_eq!(smalpha(""), None);
+        assert_eq!(smalpha(&".".repeat(83)), None);
+        assert_eq!(smalpha_all(&"x".repeat(82)).next(), None);
+    }
+
+    fn check_result(input: &str, result: &Permutation) {
+        let result = result.to_string();
+        assert_eq!(result.len(), 26);
+        assert_eq!(
+            result.chars().collect::<HashSet<_>>(),
+            (b'a'..=b'z').map(char::from).collect::<HashSet<_>>()
+        );
+        assert_eq!(smorse(&result), input);
+    }
+
+    #[test]
+    fn test_smalpha() {
+        let input =
+            "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--";
+        let result = smalpha(input);
+        assert!(result.is_some());
+        check_result(input, &result.unwrap());
+        assert_eq!(result.unwrap().code(), input);
+    }
+
+    #[test]
+    fn test_smalpha_all() {
+        let input =
+            "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--";
+        let result = smalpha_all(input).next();
+        assert!(result.is_some());
+        check_result(input, &result.unwrap());
+    }
+
+    #[test]
+    fn test_smalpha_compare() {
+        let input =
+            "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--";
+        let r1 = smalpha(input);
+        let r2 = smalpha_all(input).next();
+        assert_eq!(r1, r2);
+    }
+
+    #[test]
+    fn test_smalpha_state() {
+        let input =
+            "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--";
+        let results = smalpha_all(input).take(2).collect::<Vec<_>>();
+        assert_eq!(results.len(), 2);
+        assert_ne!(results[0], results[1]);
+    }
+
+    #[test]
+    #[ignore]
+    // this may take a while...
+    fn test_smalpha_container() {
+        let input =
+            ".--...-.-.-.....-.--........----.-.-..---.---.--.--.-.-....-..-...-.---..--.----..";
+        let results = smalpha_all(input).collect::<Vec<_>>();
+        println!("results:");
+        for result in &results {
+            println!("  {}", result);
+        }
+        for result in &results {
+            check_result(input, result);
+        }
+        let expect: Permutation = "wirnbfzehatqlojpgcvusyxkmd".parse().unwrap();
+        assert!(results.contains(&expect));
+    }
+
+    #[test]
+    #[ignore]
+    // this may take a while...
+    fn test_smalpha_count() {
+        let input =
+            "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--";
+        assert_eq!(smalpha_all(input).count(), 41);
+    }
+
+    #[test]
+    fn test_smalpha_budget() {
+        let input =
+            "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--";
+        let options = SearchOptions {
+            node_budget: Some(10),
+            ..SearchOptions::default()
+        };
+        let outcome = smalpha_with(input, &options);
+        assert_eq!(outcome.found, None);
+        assert_eq!(outcome.status, SearchStatus::BudgetExhausted);
+
+        let outcome = smalpha_with(input, &SearchOptions::default());
+        assert!(outcome.found.is_some());
+        assert_eq!(outcome.status, SearchStatus::Finished);
+    }
+
+    #[test]
+    fn test_smalpha_all_budget() {
+        let input =
+            "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--";
+        let full = smalpha_all(input).collect::<Vec<_>>();
+
+        let mut search = smalpha_all_with(
+            input,
+            &SearchOptions {
+                node_budget: Some(20_000),
+                ..SearchOptions::default()
+            },
+        );
+        let partial = search.by_ref().collect::<Vec<_>>();
+        assert_eq!(search.status(), Some(SearchStatus::BudgetExhausted));
+        assert!(partial.len() < full.len());
+        assert_eq!(partial[..], full[..partial.len()]);
+    }
+
+    #[test]
+    fn test_smalpha_all_cancelled() {
+        let input =
+            "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--";
+        let token = CancelToken::new();
+        token.cancel();
+        let mut search = smalpha_all_with(
+            input,
+            &SearchOptions {
+                cancel: Some(token),
+                ..SearchOptions::default()
+            },
+        );
+        assert_eq!(search.status(), None);
+        assert_eq!(search.next(), None);
+        assert_eq!(search.status(), Some(SearchStatus::Cancelled));
+    }
+
+    #[test]
+    fn test_smalpha_all_stats() {
+        let input =
+            "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--";
+        let mut search = smalpha_all_with(
+            input,
+            &SearchOptions {
+                stats: true,
+                ..SearchOptions::default()
+            },
+        );
+        assert_eq!(search.by_ref().count(), 41);
+        let stats = search.stats().unwrap();
+        assert_eq!(stats.solutions, 41);
+        assert_eq!(stats.letter_successes.iter().sum::<u64>(), 41 * 26);
+        assert!(stats.nodes > 41 * 26);
+        assert!(stats.backtracks.iter().sum::<u64>() > 0);
+        assert!(stats.time_to_first_solution.unwrap() <= stats.total_time);
+
+        assert_eq!(
+            smalpha_all_with(input, &SearchOptions::default()).stats(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_smalpha_budget_after_last_letter() {
+        let input =
+            "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--";
+        let first = |budget: u64| {
+            let mut search = smalpha_all_with(
+                input,
+                &SearchOptions {
+                    node_budget: Some(budget),
+                    ..SearchOptions::default()
+                },
+            );
+            (search.next().is_some(), search.status())
+        };
+        // the smallest budget which finds a result spends its last node after
+        // the 26th letter is placed, so one less runs out right there
+        let (mut lo, mut hi) = (1, 1_000_000);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if first(mid).0 {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        assert!(first(lo).0);
+        assert_eq!(first(lo - 1), (false, Some(SearchStatus::BudgetExhausted)));
+    }
+
+    #[test]
+    fn test_smalpha_exhausted() {
+        let input =
+            "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--";
+        let mut search = smalpha_all(input);
+        assert_eq!(search.by_ref().count(), 41);
+        assert_eq!(search.next(), None);
+    }
+}
@@ -0,0 +1,369 @@
+use crate::permutation::Permutation;
+use crate::search::{serialize_secs, CancelToken, SearchOptions, SearchStats, SearchStatus};
+use crate::{check_alphabet_code, smalpha_all_with, smalpha_with};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Which solutions to find for each input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolutionMode {
+    /// only the first solution
+    First,
+    /// every solution
+    All,
+    /// count every solution, but don't keep them
+    Count,
+}
+
+impl FromStr for SolutionMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SolutionMode, String> {
+        match s {
+            "first" => Ok(SolutionMode::First),
+            "all" => Ok(SolutionMode::All),
+            "count" => Ok(SolutionMode::Count),
+            _ => Err(format!(
+                "unknown solution mode {:?}: expect first, all, or count",
+                s
+            )),
+        }
+    }
+}
+
+/// How to write batch results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// human-readable lines
+    Text,
+    /// one JSON object per line
+    JsonLines,
+    /// comma-separated values with a header row
+    Csv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<OutputFormat, String> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "jsonl" => Ok(OutputFormat::JsonLines),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(format!(
+                "unknown output format {:?}: expect text, jsonl, or csv",
+                s
+            )),
+        }
+    }
+}
+
+/// Settings which apply to every line of a batch.
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    pub mode: SolutionMode,
+    /// node budget for each line
+    pub node_budget: Option<u64>,
+    /// time limit for each line, counted from when that line starts
+    pub timeout: Option<Duration>,
+    /// cancels every line still running
+    pub cancel: Option<CancelToken>,
+    /// collect `SearchStats` for each line
+    pub stats: bool,
+}
+
+impl Default for BatchOptions {
+    fn default() -> BatchOptions {
+        BatchOptions {
+            mode: SolutionMode::First,
+            node_budget: None,
+            timeout: None,
+            cancel: None,
+            stats: false,
+        }
+    }
+}
+
+impl BatchOptions {
+    fn search_options(&self) -> SearchOptions {
+        let options = SearchOptions {
+            node_budget: self.node_budget,
+            cancel: self.cancel.clone(),
+            stats: self.stats,
+            ..SearchOptions::default()
+        };
+        match self.timeout {
+            Some(timeout) => options.with_timeout(timeout),
+            None => options,
+        }
+    }
+}
+
+/// The outcome of solving one line of a batch.
+#[derive(Debug, Clone, Serialize)]
+pub struct LineResult {
+    /// 1-based line number in the input
+    pub line: usize,
+    pub input: String,
+    /// why the input can't be the code for any alphabet, if it can't
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// why the search stopped; `None` if no search was attempted
+    pub status: Option<SearchStatus>,
+    /// how many solutions were found
+    pub count: usize,
+    /// the solutions found; `None` in `SolutionMode::Count`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solutions: Option<Vec<Permutation>>,
+    #[serde(serialize_with = "serialize_secs")]
+    pub elapsed: Duration,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<SearchStats>,
+}
+
+fn solve_line(line: usize, input: &str, options: &BatchOptions) -> LineResult {
+    let start = Instant::now();
+    let mut result = LineResult {
+        line,
+        input: input.to_string(),
+        error: None,
+        status: None,
+        count: 0,
+        solutions: None,
+        elapsed: Duration::default(),
+        stats: None,
+    };
+    if let Err(err) = check_alphabet_code(input) {
+        result.error = Some(err.to_string());
+        result.elapsed = start.elapsed();
+        return result;
+    }
+
+    let search_options = options.search_options();
+    match options.mode {
+        SolutionMode::First => {
+            let outcome = smalpha_with(input, &search_options);
+            result.status = Some(outcome.status);
+            result.solutions = Some(outcome.found.into_iter().collect());
+            result.stats = outcome.stats;
+        }
+        SolutionMode::All | SolutionMode::Count => {
+            let mut search = smalpha_all_with(input, &search_options);
+            if options.mode == SolutionMode::All {
+                result.solutions = Some(search.by_ref().collect());
+            } else {
+                result.count = search.by_ref().count();
+            }
+            result.status = search.status();
+            result.stats = search.stats();
+        }
+    }
+    if let Some(solutions) = &result.solutions {
+        result.count = solutions.len();
+    }
+    result.elapsed = start.elapsed();
+    result
+}
+
+/// Solve every input in parallel.
+///
+/// Results are returned in the same order as the inputs.
+pub fn solve_batch<S>(inputs: &[S], options: &BatchOptions) -> Vec<LineResult>
+where
+    S: AsRef<str> + Sync,
+{
+    inputs
+        .par_iter()
+        .enumerate()
+        .map(|(idx, input)| solve_line(idx + 1, input.as_ref().trim(), options))
+        .collect()
+}
+
+impl fmt::Display for LineResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} -> ", self.input)?;
+        if let Some(err) = &self.error {
+            return write!(f, "invalid input: {}", err);
+        }
+        match &self.solutions {
+            Some(solutions) if solutions.is_empty() => {
+                write!(f, "no permutation found for this alphabet")?
+            }
+            Some(solutions) if solutions.len() == 1 => write!(f, "{}", solutions[0])?,
+            Some(solutions) => {
+                write!(f, "{} solutions:", solutions.len())?;
+                for solution in solutions {
+                    write!(f, " {}", solution)?;
+                }
+            }
+            None => write!(f, "{} solutions", self.count)?,
+        }
+        if let Some(status) = self.status {
+            if status != SearchStatus::Finished {
+                write!(f, " [{}]", status)?;
+            }
+        }
+        write!(f, " ({:?})", self.elapsed)
+    }
+}
+
+/// Quote a CSV field if it needs it.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Write `results` to `out` in the requested format.
+pub fn write_results<W: Write>(
+    out: &mut W,
+    results: &[LineResult],
+    format: OutputFormat,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for result in results {
+                writeln!(out, "{}", result)?;
+                if let Some(stats) = &result.stats {
+                    writeln!(out, "{}", stats)?;
+                }
+            }
+        }
+        OutputFormat::JsonLines => {
+            for result in results {
+                serde_json::to_writer(&mut *out, result)?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "line,input,status,error,count,solutions,elapsed")?;
+            for result in results {
+                let solutions = result
+                    .solutions
+                    .iter()
+                    .flatten()
+                    .map(Permutation::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{}",
+                    result.line,
+                    csv_field(&result.input),
+                    result.status.map(|s| s.name()).unwrap_or(""),
+                    csv_field(result.error.as_deref().unwrap_or("")),
+                    result.count,
+                    solutions,
+                    result.elapsed.as_secs_f64(),
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str =
+        "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--";
+
+    fn inputs() -> Vec<&'static str> {
+        vec![INPUT, "-.-", INPUT]
+    }
+
+    #[test]
+    fn test_order_and_errors() {
+        let results = solve_batch(&inputs(), &BatchOptions::default());
+        assert_eq!(
+            results.iter().map(|r| r.line).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(results[0].count, 1);
+        assert_eq!(results[0].status, Some(SearchStatus::Finished));
+        assert!(results[1].error.is_some());
+        assert_eq!(results[1].status, None);
+        assert_eq!(results[2].solutions, results[0].solutions);
+    }
+
+    #[test]
+    fn test_modes() {
+        let all = solve_batch(
+            &[INPUT],
+            &BatchOptions {
+                mode: SolutionMode::All,
+                ..BatchOptions::default()
+            },
+        );
+        assert_eq!(all[0].count, 41);
+        assert_eq!(all[0].solutions.as_ref().unwrap().len(), 41);
+
+        let count = solve_batch(
+            &[INPUT],
+            &BatchOptions {
+                mode: SolutionMode::Count,
+                ..BatchOptions::default()
+            },
+        );
+        assert_eq!(count[0].count, 41);
+        assert_eq!(count[0].solutions, None);
+    }
+
+    #[test]
+    fn test_budget() {
+        let results = solve_batch(
+            &[INPUT],
+            &BatchOptions {
+                mode: SolutionMode::Count,
+                node_budget: Some(100),
+                ..BatchOptions::default()
+            },
+        );
+        assert_eq!(results[0].status, Some(SearchStatus::BudgetExhausted));
+    }
+
+    #[test]
+    fn test_jsonl() {
+        let results = solve_batch(&inputs(), &BatchOptions::default());
+        let mut out = Vec::new();
+        write_results(&mut out, &results, OutputFormat::JsonLines).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        let first: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["line"], 1);
+        assert_eq!(first["status"], "finished");
+        assert_eq!(first["solutions"][0], "esfaboluzpvcwdijrmxqhgktny");
+        let second: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert!(second["error"].is_string());
+    }
+
+    #[test]
+    fn test_csv() {
+        let results = solve_batch(&inputs(), &BatchOptions::default());
+        let mut out = Vec::new();
+        write_results(&mut out, &results, OutputFormat::Csv).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with(&format!(
+            "1,{},finished,,1,esfaboluzpvcwdijrmxqhgktny,",
+            INPUT
+        )));
+        assert!(lines[2].starts_with("2,-.-,,"));
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("abc"), "abc");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
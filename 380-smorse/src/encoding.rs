@@ -0,0 +1,371 @@
+use core::fmt;
+use core::str::Chars;
+
+/// The code for each letter, `a` through `z`.
+pub const MORSE: [&str; 26] = [
+    ".-", "-...", "-.-.", "-..", ".", "..-.", "--.", "....", "..", ".---", "-.-", ".-..", "--",
+    "-.", "---", ".--.", "--.-", ".-.", "...", "-", "..-", "...-", ".--", "-..-", "-.--", "--..",
+];
+
+/// The code for `c`, or `None` if it is not in `a..=z`.
+pub fn letter_code(c: char) -> Option<&'static str> {
+    match c {
+        'a'..='z' => Some(MORSE[c as usize - 'a' as usize]),
+        _ => None,
+    }
+}
+
+/// Codes for letters outside `a..=z`, as used for some European languages.
+///
+/// Several letters share a code: `ä` and `æ`, `à` and `å`, and `ö`, `ø` and `ó`.
+pub const EXTENDED: [(char, &str); 14] = [
+    ('à', ".--.-"),
+    ('å', ".--.-"),
+    ('ä', ".-.-"),
+    ('æ', ".-.-"),
+    ('ç', "-.-.."),
+    ('ð', "..--."),
+    ('è', ".-..-"),
+    ('é', "..-.."),
+    ('ñ', "--.--"),
+    ('ó', "---."),
+    ('ö', "---."),
+    ('ø', "---."),
+    ('þ', ".--.."),
+    ('ü', "..--"),
+];
+
+/// The code for the digraph `ch`, which in extended morse is a single letter.
+pub const CH: &str = "----";
+
+/// The extended code for `c`, or `None` if it has none.
+///
+/// This doesn't cover `a..=z`; see `letter_code` for those.
+pub fn extended_code(c: char) -> Option<&'static str> {
+    EXTENDED
+        .iter()
+        .find(|&&(letter, _)| letter == c)
+        .map(|&(_, code)| code)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symbol {
+    Dot,
+    Dash,
+}
+
+impl Symbol {
+    /// How long the key is held down for this symbol, in dot lengths.
+    pub fn units(self) -> u8 {
+        match self {
+            Symbol::Dot => 1,
+            Symbol::Dash => 3,
+        }
+    }
+}
+
+/// A sequence of up to 128 dots and dashes, packed into a single integer.
+///
+/// Bit `i` is set when the symbol at offset `i` is a dash. This makes it cheap
+/// to ask whether one sequence appears at some offset within another.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Sequence {
+    bits: u128,
+    len: u8,
+}
+
+/// The packed code for each letter, `a` through `z`.
+pub const LETTERS: [Sequence; 26] = {
+    let mut letters = [Sequence::new(); 26];
+    let mut idx = 0;
+    while idx < 26 {
+        letters[idx] = match Sequence::from_code(MORSE[idx]) {
+            Some(seq) => seq,
+            None => panic!("letter codes are all dots and dashes"),
+        };
+        idx += 1;
+    }
+    letters
+};
+
+impl Sequence {
+    pub const CAPACITY: usize = 128;
+
+    pub const fn new() -> Sequence {
+        Sequence { bits: 0, len: 0 }
+    }
+
+    /// Pack a code made of `.` and `-`.
+    ///
+    /// Returns `None` if it contains anything else or is longer than `CAPACITY`.
+    pub const fn from_code(code: &str) -> Option<Sequence> {
+        let code = code.as_bytes();
+        if code.len() > Sequence::CAPACITY {
+            return None;
+        }
+        let mut bits = 0;
+        let mut idx = 0;
+        while idx < code.len() {
+            match code[idx] {
+                b'.' => {}
+                b'-' => bits |= 1 << idx,
+                _ => return None,
+            }
+            idx += 1;
+        }
+        Some(Sequence {
+            bits,
+            len: code.len() as u8,
+        })
+    }
+
+    /// The squashed code of `text`; anything other than `a..=z` is dropped.
+    ///
+    /// Returns `None` if the code would be longer than `CAPACITY`.
+    pub fn from_letters(text: &str) -> Option<Sequence> {
+        let mut seq = Sequence::new();
+        for c in text.chars() {
+            if let 'a'..='z' = c {
+                seq = seq.concat(&LETTERS[c as usize - 'a' as usize])?;
+            }
+        }
+        Some(seq)
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The packed symbols: bit `i` is set when symbol `i` is a dash.
+    pub const fn bits(&self) -> u128 {
+        self.bits
+    }
+
+    pub fn get(&self, idx: usize) -> Option<Symbol> {
+        if idx >= self.len() {
+            None
+        } else if self.bits & (1 << idx) != 0 {
+            Some(Symbol::Dash)
+        } else {
+            Some(Symbol::Dot)
+        }
+    }
+
+    /// Append a symbol, or return `false` if the sequence is already full.
+    pub fn push(&mut self, symbol: Symbol) -> bool {
+        if self.len() == Sequence::CAPACITY {
+            return false;
+        }
+        if symbol == Symbol::Dash {
+            self.bits |= 1 << self.len;
+        }
+        self.len += 1;
+        true
+    }
+
+    /// This sequence followed by `other`, or `None` if that would not fit.
+    pub fn concat(&self, other: &Sequence) -> Option<Sequence> {
+        if self.len() + other.len() > Sequence::CAPACITY {
+            return None;
+        }
+        Some(Sequence {
+            bits: self.bits | other.bits.checked_shl(self.len as u32).unwrap_or(0),
+            len: self.len + other.len,
+        })
+    }
+
+    /// Whether `other` appears in this sequence starting at `offset`.
+    pub fn matches_at(&self, offset: usize, other: &Sequence) -> bool {
+        if offset + other.len() > self.len() {
+            return false;
+        }
+        let mask = match 1_u128.checked_shl(other.len as u32) {
+            Some(bit) => bit - 1,
+            None => !0,
+        };
+        (self.bits >> offset) & mask == other.bits
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Symbol> + '_ {
+        (0..self.len()).filter_map(move |idx| self.get(idx))
+    }
+}
+
+impl fmt::Display for Sequence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for symbol in self.iter() {
+            match symbol {
+                Symbol::Dot => f.write_str(".")?,
+                Symbol::Dash => f.write_str("-")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One step of keying: the key is held down or released for some number of dot lengths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Element {
+    pub key_down: bool,
+    pub units: u8,
+}
+
+/// Gap between the symbols of a letter, in dot lengths.
+const SYMBOL_GAP: u8 = 1;
+/// Gap between letters, in dot lengths.
+const LETTER_GAP: u8 = 3;
+/// Gap between words, in dot lengths.
+const WORD_GAP: u8 = 7;
+
+/// The keying timeline for some text, using standard morse timing.
+///
+/// Letters are `a..=z`; any run of whitespace between letters separates words,
+/// and anything else is dropped. No gap is emitted before the first or after
+/// the last symbol.
+pub struct Timeline<'a> {
+    chars: Chars<'a>,
+    code: &'static [u8],
+    pos: usize,
+    gap: u8,
+    started: bool,
+    word_break: bool,
+}
+
+impl<'a> Timeline<'a> {
+    pub fn new(text: &'a str) -> Timeline<'a> {
+        Timeline {
+            chars: text.chars(),
+            code: &[],
+            pos: 0,
+            gap: 0,
+            started: false,
+            word_break: false,
+        }
+    }
+}
+
+impl<'a> Iterator for Timeline<'a> {
+    type Item = Element;
+
+    fn next(&mut self) -> Option<Element> {
+        loop {
+            if self.pos < self.code.len() {
+                if self.gap > 0 {
+                    let units = self.gap;
+                    self.gap = 0;
+                    return Some(Element {
+                        key_down: false,
+                        units,
+                    });
+                }
+                let symbol = if self.code[self.pos] == b'-' {
+                    Symbol::Dash
+                } else {
+                    Symbol::Dot
+                };
+                self.pos += 1;
+                if self.pos < self.code.len() {
+                    self.gap = SYMBOL_GAP;
+                }
+                return Some(Element {
+                    key_down: true,
+                    units: symbol.units(),
+                });
+            }
+
+            let c = self.chars.next()?;
+            match letter_code(c) {
+                Some(code) => {
+                    if self.started {
+                        self.gap = if self.word_break {
+                            WORD_GAP
+                        } else {
+                            LETTER_GAP
+                        };
+                    }
+                    self.started = true;
+                    self.word_break = false;
+                    self.code = code.as_bytes();
+                    self.pos = 0;
+                }
+                None if c.is_whitespace() && self.started => self.word_break = true,
+                None => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn on(units: u8) -> Element {
+        Element {
+            key_down: true,
+            units,
+        }
+    }
+
+    fn off(units: u8) -> Element {
+        Element {
+            key_down: false,
+            units,
+        }
+    }
+
+    #[test]
+    fn test_letter_code() {
+        assert_eq!(letter_code('a'), Some(".-"));
+        assert_eq!(letter_code('z'), Some("--.."));
+        assert_eq!(letter_code('A'), None);
+        assert_eq!(extended_code('ñ'), Some("--.--"));
+        assert_eq!(extended_code('a'), None);
+        for &(_, code) in EXTENDED.iter() {
+            assert!(!MORSE.contains(&code));
+        }
+    }
+
+    #[test]
+    fn test_sequence() {
+        let sos = Sequence::from_letters("sos").unwrap();
+        assert_eq!(sos, Sequence::from_code("...---...").unwrap());
+        assert_eq!(sos.len(), 9);
+        assert_eq!(sos.get(0), Some(Symbol::Dot));
+        assert_eq!(sos.get(3), Some(Symbol::Dash));
+        assert_eq!(sos.get(9), None);
+        assert!(sos.matches_at(3, &LETTERS[(b'o' - b'a') as usize]));
+        assert!(!sos.matches_at(0, &LETTERS[(b'o' - b'a') as usize]));
+        assert!(!sos.matches_at(8, &LETTERS[(b'o' - b'a') as usize]));
+        assert_eq!(Sequence::from_code(".-x"), None);
+    }
+
+    #[test]
+    fn test_sequence_capacity() {
+        let mut seq = Sequence::new();
+        for _ in 0..Sequence::CAPACITY {
+            assert!(seq.push(Symbol::Dash));
+        }
+        assert!(!seq.push(Symbol::Dot));
+        assert!(seq.matches_at(0, &seq));
+        assert_eq!(seq.concat(&LETTERS[0]), None);
+        assert_eq!(
+            Sequence::from_letters("oooooooooooooooooooooooooooooooooooooooooooo"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_timeline() {
+        let timeline = Timeline::new("et e");
+        let expect = [on(1), off(3), on(3), off(7), on(1)];
+        assert!(timeline.eq(expect.iter().cloned()));
+
+        let timeline = Timeline::new(" a! ");
+        let expect = [on(1), off(1), on(3)];
+        assert!(timeline.eq(expect.iter().cloned()));
+    }
+}
@@ -0,0 +1,223 @@
+use crate::search::SearchOptions;
+use crate::stream::{decode_spaced, encode_spaced};
+use crate::{smalpha, smalpha_all_with, smorse, AlphaSearch};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+
+/// Borrow a C string as a `&str`, or `None` if it is null or not UTF-8.
+///
+/// # Safety
+///
+/// `s` must be null or point to a nul-terminated string which outlives `'a`.
+unsafe fn borrow_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+/// Hand a string to the caller, who must release it with `smorse_string_free`.
+fn give_string(s: String) -> *mut c_char {
+    match CString::new(s) {
+        Ok(s) => s.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Encode `text` as squashed morse.
+///
+/// Returns null if `text` is null or not UTF-8.
+///
+/// # Safety
+///
+/// `text` must be null or a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn smorse_encode(text: *const c_char) -> *mut c_char {
+    match borrow_str(text) {
+        Some(text) => give_string(smorse(text)),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Encode `text` as spaced morse, which unlike squashed morse can be decoded.
+///
+/// Returns null if `text` is null or not UTF-8.
+///
+/// # Safety
+///
+/// `text` must be null or a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn smorse_encode_spaced(text: *const c_char) -> *mut c_char {
+    match borrow_str(text) {
+        Some(text) => give_string(encode_spaced(text)),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Decode spaced morse.
+///
+/// Returns null if `code` is null or is not valid spaced morse.
+///
+/// # Safety
+///
+/// `code` must be null or a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn smorse_decode(code: *const c_char) -> *mut c_char {
+    match borrow_str(code).map(decode_spaced) {
+        Some(Ok(text)) => give_string(text),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Find the first permutation of the alphabet whose squashed code is `code`.
+///
+/// Returns null if there is none, or if `code` is null or not UTF-8.
+///
+/// # Safety
+///
+/// `code` must be null or a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn smorse_smalpha(code: *const c_char) -> *mut c_char {
+    match borrow_str(code).and_then(smalpha) {
+        Some(perm) => give_string(perm.to_string()),
+        None => ptr::null_mut(),
+    }
+}
+
+/// An in-progress search for every permutation of the alphabet which produces a code.
+pub struct SmorseSmalphaIter(AlphaSearch);
+
+/// Start searching for every permutation of the alphabet whose squashed code is `code`.
+///
+/// Returns null if `code` is null or not UTF-8. Otherwise, the caller must
+/// release the result with `smorse_smalpha_free`.
+///
+/// # Safety
+///
+/// `code` must be null or a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn smorse_smalpha_all(code: *const c_char) -> *mut SmorseSmalphaIter {
+    match borrow_str(code) {
+        Some(code) => Box::into_raw(Box::new(SmorseSmalphaIter(smalpha_all_with(
+            code,
+            &SearchOptions::default(),
+        )))),
+        None => ptr::null_mut(),
+    }
+}
+
+/// The next solution from `iter`, or null once there are no more.
+///
+/// # Safety
+///
+/// `iter` must be null or have come from `smorse_smalpha_all` and not yet been freed.
+#[no_mangle]
+pub unsafe extern "C" fn smorse_smalpha_next(iter: *mut SmorseSmalphaIter) -> *mut c_char {
+    match iter.as_mut().and_then(|iter| iter.0.next()) {
+        Some(perm) => give_string(perm.to_string()),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Release a search started by `smorse_smalpha_all`. Null is ignored.
+///
+/// # Safety
+///
+/// `iter` must be null or have come from `smorse_smalpha_all` and not yet been freed.
+#[no_mangle]
+pub unsafe extern "C" fn smorse_smalpha_free(iter: *mut SmorseSmalphaIter) {
+    if !iter.is_null() {
+        drop(Box::from_raw(iter));
+    }
+}
+
+/// Release a string returned by any `smorse_` function. Null is ignored.
+///
+/// # Safety
+///
+/// `s` must be null or have come from this library and not yet been freed.
+#[no_mangle]
+pub unsafe extern "C" fn smorse_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Allocate `len` bytes, for hosts such as WebAssembly runtimes which can't
+/// otherwise place strings in our memory. Release them with `smorse_dealloc`.
+#[no_mangle]
+pub extern "C" fn smorse_alloc(len: usize) -> *mut u8 {
+    let mut buf = Vec::<u8>::with_capacity(len);
+    let ptr = buf.as_mut_ptr();
+    std::mem::forget(buf);
+    ptr
+}
+
+/// Release memory from `smorse_alloc`. Does nothing if `ptr` is null.
+///
+/// # Safety
+///
+/// `ptr` must be null, or have come from `smorse_alloc(len)` and not yet been
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn smorse_dealloc(ptr: *mut u8, len: usize) {
+    if ptr.is_null() {
+        return;
+    }
+    drop(Vec::from_raw_parts(ptr, 0, len));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cstr(s: &str) -> CString {
+        CString::new(s).unwrap()
+    }
+
+    /// Take ownership of a string from the library, as a C caller would.
+    unsafe fn take(s: *mut c_char) -> Option<String> {
+        if s.is_null() {
+            return None;
+        }
+        let out = CStr::from_ptr(s).to_str().unwrap().to_string();
+        smorse_string_free(s);
+        Some(out)
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        unsafe {
+            assert_eq!(
+                take(smorse_encode(cstr("sos").as_ptr())),
+                Some("...---...".into())
+            );
+            let spaced = smorse_encode_spaced(cstr("sos me").as_ptr());
+            assert_eq!(take(smorse_decode(spaced)), Some("sos me".into()));
+            smorse_string_free(spaced);
+            assert_eq!(take(smorse_decode(cstr("..--..--").as_ptr())), None);
+            assert_eq!(take(smorse_encode(ptr::null())), None);
+        }
+    }
+
+    #[test]
+    fn test_smalpha() {
+        let code = CString::new(
+            "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--",
+        )
+        .unwrap();
+        unsafe {
+            let first = take(smorse_smalpha(code.as_ptr()));
+            let iter = smorse_smalpha_all(code.as_ptr());
+            let mut all = Vec::new();
+            while let Some(perm) = take(smorse_smalpha_next(iter)) {
+                all.push(perm);
+            }
+            smorse_smalpha_free(iter);
+            assert_eq!(all.len(), 41);
+            assert_eq!(first.as_ref(), all.first());
+            assert_eq!(take(smorse_smalpha(cstr("-.-").as_ptr())), None);
+        }
+    }
+}
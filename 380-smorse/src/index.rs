@@ -0,0 +1,255 @@
+use crate::words::Encoded;
+use std::collections::HashMap;
+
+/// A lookup from codes to the words which encode as them.
+pub struct CodeIndex<'a> {
+    words: &'a [Encoded],
+    by_code: HashMap<&'a str, Vec<usize>>,
+    /// every distinct code length, ascending
+    lengths: Vec<usize>,
+    /// every distinct code, sorted
+    codes: Vec<&'a str>,
+    /// every distinct code reversed, sorted, with the code itself
+    reversed: Vec<(String, &'a str)>,
+}
+
+impl<'a> CodeIndex<'a> {
+    pub fn new(words: &'a [Encoded]) -> CodeIndex<'a> {
+        let mut by_code: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (idx, word) in words.iter().enumerate() {
+            if !word.code.is_empty() {
+                by_code.entry(&word.code).or_default().push(idx);
+            }
+        }
+        let mut lengths = by_code.keys().map(|code| code.len()).collect::<Vec<_>>();
+        lengths.sort_unstable();
+        lengths.dedup();
+        let mut codes = by_code.keys().cloned().collect::<Vec<_>>();
+        codes.sort_unstable();
+        let mut reversed = codes
+            .iter()
+            .map(|&code| (code.chars().rev().collect(), code))
+            .collect::<Vec<(String, _)>>();
+        reversed.sort_unstable();
+        CodeIndex {
+            words,
+            by_code,
+            lengths,
+            codes,
+            reversed,
+        }
+    }
+
+    /// The indexed words.
+    pub fn words(&self) -> &'a [Encoded] {
+        self.words
+    }
+
+    /// The words whose code is exactly `code`.
+    pub fn lookup(&self, code: &str) -> impl Iterator<Item = &'a Encoded> + '_ {
+        self.by_code
+            .get(code)
+            .into_iter()
+            .flatten()
+            .map(move |&idx| &self.words[idx])
+    }
+
+    /// Every distinct code length, ascending.
+    pub fn lengths(&self) -> &[usize] {
+        &self.lengths
+    }
+
+    /// Every distinct code which starts with `prefix`, sorted.
+    pub fn codes_with_prefix(&self, prefix: &str) -> &[&'a str] {
+        let lo = self.codes.partition_point(|&code| code < prefix);
+        let len = self.codes[lo..].partition_point(|code| code.starts_with(prefix));
+        &self.codes[lo..lo + len]
+    }
+
+    /// Every distinct code which ends with `suffix`, sorted by their reversals.
+    pub fn codes_with_suffix(&self, suffix: &str) -> impl Iterator<Item = &'a str> + '_ {
+        let rev = suffix.chars().rev().collect::<String>();
+        let lo = self.reversed.partition_point(|(r, _)| *r < rev);
+        let len = self.reversed[lo..].partition_point(|(r, _)| r.starts_with(&rev));
+        self.reversed[lo..lo + len].iter().map(|&(_, code)| code)
+    }
+
+    /// Whether any word's code is exactly `code`.
+    pub fn contains_code(&self, code: &str) -> bool {
+        self.by_code.contains_key(code)
+    }
+
+    /// For each offset of `code`, the fewest words whose codes make up the rest of it.
+    ///
+    /// `None` means the rest can't be made of words at all.
+    fn fewest_words(&self, code: &str) -> Vec<Option<usize>> {
+        let mut fewest = vec![None; code.len() + 1];
+        fewest[code.len()] = Some(0);
+        for offset in (0..code.len()).rev() {
+            fewest[offset] = self
+                .lengths
+                .iter()
+                .take_while(|&&len| offset + len <= code.len())
+                .filter(|&&len| self.contains_code(&code[offset..offset + len]))
+                .filter_map(|&len| fewest[offset + len])
+                .min()
+                .map(|n| n + 1);
+        }
+        fewest
+    }
+
+    /// Every phrase of two to `max_words` words whose squashed code is `code`.
+    ///
+    /// Phrases are sorted by their words.
+    pub fn phrases(&self, code: &str, max_words: usize) -> Vec<Vec<&'a Encoded>> {
+        let fewest = self.fewest_words(code);
+        let mut pieces = Vec::new();
+        let mut out = Vec::new();
+        self.split(code, 0, max_words, &fewest, &mut pieces, &mut out);
+        out.sort_by(|a: &Vec<&Encoded>, b| {
+            a.iter().map(|e| &e.word).cmp(b.iter().map(|e| &e.word))
+        });
+        out
+    }
+
+    /// Split `code[offset..]` into pieces which are each some word's code, then
+    /// add every choice of words for those pieces to `out`.
+    fn split<'c>(
+        &self,
+        code: &'c str,
+        offset: usize,
+        words_left: usize,
+        fewest: &[Option<usize>],
+        pieces: &mut Vec<&'c str>,
+        out: &mut Vec<Vec<&'a Encoded>>,
+    ) {
+        if offset == code.len() {
+            if pieces.len() >= 2 {
+                self.expand(pieces, &mut Vec::new(), out);
+            }
+            return;
+        }
+        for &len in &self.lengths {
+            let end = offset + len;
+            if end > code.len() {
+                break;
+            }
+            match fewest[end] {
+                Some(rest) if rest < words_left => {}
+                _ => continue,
+            }
+            let piece = &code[offset..end];
+            if self.contains_code(piece) {
+                pieces.push(piece);
+                self.split(code, end, words_left - 1, fewest, pieces, out);
+                pieces.pop();
+            }
+        }
+    }
+
+    /// Add every choice of one word per piece to `out`.
+    fn expand(
+        &self,
+        pieces: &[&str],
+        phrase: &mut Vec<&'a Encoded>,
+        out: &mut Vec<Vec<&'a Encoded>>,
+    ) {
+        match pieces.split_first() {
+            None => out.push(phrase.clone()),
+            Some((piece, rest)) => {
+                for word in self.lookup(piece) {
+                    phrase.push(word);
+                    self.expand(rest, phrase, out);
+                    phrase.pop();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn words() -> Vec<Encoded> {
+        [
+            "bits",
+            "three",
+            "pro",
+            "grammer",
+            "programmer",
+            "sos",
+            "so",
+            "s",
+            "e",
+            "i",
+        ]
+        .iter()
+        .map(|w| Encoded::new(w))
+        .collect()
+    }
+
+    fn phrase_words(phrases: Vec<Vec<&Encoded>>) -> Vec<String> {
+        phrases
+            .iter()
+            .map(|p| {
+                p.iter()
+                    .map(|e| e.word.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lookup() {
+        let words = words();
+        let index = CodeIndex::new(&words);
+        let mut bits = index
+            .lookup(&words[0].code)
+            .map(|e| &e.word)
+            .collect::<Vec<_>>();
+        bits.sort();
+        assert_eq!(bits, vec!["bits", "three"]);
+        assert_eq!(index.lookup("--------").count(), 0);
+    }
+
+    #[test]
+    fn test_affixes() {
+        let words = words();
+        let index = CodeIndex::new(&words);
+        for affix in &["", ".", "..", "-", ".-.", "--------"] {
+            let mut starts = index.codes.clone();
+            starts.retain(|code| code.starts_with(affix));
+            assert_eq!(index.codes_with_prefix(affix), &starts[..], "{}", affix);
+            let mut ends = index.codes.clone();
+            ends.retain(|code| code.ends_with(affix));
+            let mut found = index.codes_with_suffix(affix).collect::<Vec<_>>();
+            found.sort_unstable();
+            assert_eq!(found, ends, "{}", affix);
+        }
+    }
+
+    #[test]
+    fn test_phrases() {
+        let words = words();
+        let index = CodeIndex::new(&words);
+        let programmer = Encoded::new("programmer");
+        assert!(phrase_words(index.phrases(&programmer.code, 2)).contains(&"pro grammer".into()));
+
+        // "..." is s, e i, i e, or e e e
+        let dots = phrase_words(index.phrases(&Encoded::new("s").code, 3));
+        assert_eq!(dots, vec!["e e e", "e i", "i e"]);
+        assert!(phrase_words(index.phrases(&Encoded::new("s").code, 2))
+            .iter()
+            .all(|p| p.split(' ').count() == 2));
+    }
+
+    #[test]
+    fn test_no_phrases() {
+        let words = words();
+        let index = CodeIndex::new(&words);
+        assert!(index.phrases("-.-.-.-.-.-", 4).is_empty());
+        assert!(index.phrases(&Encoded::new("e").code, 4).is_empty());
+    }
+}
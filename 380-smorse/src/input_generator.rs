@@ -95,6 +95,12 @@ impl InputGenerator {
     }
 }
 
+impl Default for InputGenerator {
+    fn default() -> InputGenerator {
+        InputGenerator::new()
+    }
+}
+
 impl Iterator for InputGenerator {
     type Item = String;
     fn next(&mut self) -> Option<String> {
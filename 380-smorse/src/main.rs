@@ -1,223 +1,826 @@
-use counter::Counter;
-use smorse::{smalpha, smalpha_all, smorse};
-use std::collections::HashSet;
+use rayon::prelude::*;
+use serde_json::json;
+use smorse::batch::{solve_batch, write_results, BatchOptions, OutputFormat, SolutionMode};
+use smorse::index::CodeIndex;
+use smorse::normalize::{Accents, NormalizeReport, Normalizer, Punctuation};
+use smorse::search::{SearchOptions, SearchStatus};
+use smorse::stream::{decode_spaced, encode_spaced, MorseDecoder, MorseEncoder};
+use smorse::suffix::SuffixIndex;
+use smorse::symmetry::{find_symmetries, phrase_text, Symmetry, SymmetryOptions};
+use smorse::words::{Encoded, Lines, Source, Words};
+use smorse::{check_alphabet_code, smalpha, smalpha_all, smalpha_all_with, smalpha_with, smorse};
+use std::collections::BTreeMap;
 use std::error::Error;
-use std::fs::File;
+use std::fmt;
+use std::io;
 use std::io::prelude::*;
-use std::io::BufReader;
-use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use structopt::clap::AppSettings;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "smorse", about = "convert strings to squashed morse code")]
 struct Opts {
-    /// convert a lowercase string to squashed morse
-    input: Option<String>,
+    /// how to write results: text, or json (one object per line)
+    #[structopt(long, default_value = "text", raw(global = "true"))]
+    format: Format,
 
-    /// path to wordlist
-    #[structopt(short, long = "word-list", parse(from_os_str))]
-    word_list: Option<PathBuf>,
+    #[structopt(subcommand)]
+    command: Command,
+}
 
-    /// find the only sequence that's the code for 13 different words
-    #[structopt(long = "bonus-1-1")]
-    bonus_1_1: bool,
+/// How to write the results of any command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+}
 
-    /// find the only word that has 15 dashes in a row
-    #[structopt(long = "bonus-1-2")]
-    bonus_1_2: bool,
+impl FromStr for Format {
+    type Err = String;
 
-    /// find all 21-letter words with the same number of dots and dashes
-    #[structopt(long = "bonus-1-3")]
-    bonus_1_3: bool,
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format {:?}: expect text or json", s)),
+        }
+    }
+}
 
-    /// find the only 13-letter word that encodes to a palindrome
-    #[structopt(long = "bonus-1-4")]
-    bonus_1_4: bool,
+impl Format {
+    /// print one result, as `text` or as a line of `json`
+    fn print(self, text: impl fmt::Display, json: serde_json::Value) {
+        match self {
+            Format::Text => println!("{}", text),
+            Format::Json => println!("{}", json),
+        }
+    }
+}
 
-    /// find all 13-char sequences which do not appear in the encoding of any word
-    #[structopt(long = "bonus-1-5")]
-    bonus_1_5: bool,
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// encode text as morse; without TEXT, encode each line of stdin
+    #[structopt(name = "encode")]
+    Encode {
+        /// separate letters with spaces and words with ` / `, so that the code can be decoded
+        #[structopt(long)]
+        spaced: bool,
+
+        text: Option<String>,
+    },
+
+    /// decode spaced morse; without CODE, decode stdin
+    #[structopt(name = "decode", raw(setting = "AppSettings::AllowLeadingHyphen"))]
+    Decode { code: Option<String> },
+
+    /// questions about the encodings of every word in a wordlist
+    #[structopt(name = "wordlist")]
+    Wordlist(WordlistOpts),
+
+    /// search for permutations of the alphabet which produce a squashed morse value
+    #[structopt(name = "smalpha")]
+    Smalpha(SmalphaCommand),
+
+    /// long-running searches over every possible alphabet code
+    #[structopt(name = "search")]
+    Search(SearchCommand),
+}
 
-    /// search for permutations of an alphabet which produce this squashed morse value
+#[derive(Debug, StructOpt)]
+struct WordlistOpts {
+    /// path to wordlist, which may be gzip or zstd compressed, or - for stdin; repeat to merge lists
+    #[structopt(
+        short,
+        long = "word-list",
+        parse(from_os_str),
+        raw(required = "true", number_of_values = "1")
+    )]
+    word_list: Vec<Source>,
+
+    /// skip lines which are not UTF-8 instead of stopping with an error
     #[structopt(long)]
-    smalpha: Option<String>,
+    lenient: bool,
+
+    /// don't lowercase words; words with capitals are rejected
+    #[structopt(long = "keep-case")]
+    keep_case: bool,
+
+    /// what to do with accented letters: strip the accents, or keep those with extended morse codes
+    #[structopt(long, default_value = "strip")]
+    accents: Accents,
 
-    /// search for permutations of an alphabet which produce this sm value for each line in this input file
-    #[structopt(long = "smorse-file", parse(from_os_str))]
-    smorse_file: Option<PathBuf>,
+    /// what to do with anything which isn't a letter: remove it, or reject the word
+    #[structopt(long, default_value = "remove")]
+    punctuation: Punctuation,
 
-    /// find lexicographically minimal input which produces exactly one alphabet
-    #[structopt(long = "bonus-2-2")]
-    bonus_2_2: Option<Option<i128>>,
+    #[structopt(subcommand)]
+    command: WordlistCommand,
 }
 
-type Rv = Result<(), Box<dyn Error>>;
+#[derive(Debug, StructOpt)]
+enum WordlistCommand {
+    /// count the dots and dashes in the encodings of every word
+    #[structopt(name = "stats")]
+    Stats,
+
+    /// list the words whose encodings pass every filter given
+    #[structopt(name = "query")]
+    Query {
+        /// encodes exactly as this sequence
+        #[structopt(long)]
+        code: Option<String>,
+
+        /// encoding contains this sequence, e.g. 15 dashes in a row
+        #[structopt(long)]
+        contains: Option<String>,
+
+        /// has this many letters
+        #[structopt(long)]
+        letters: Option<usize>,
+
+        /// encoding has the same number of dots and dashes
+        #[structopt(long)]
+        balanced: bool,
+
+        /// encoding is a palindrome
+        #[structopt(long)]
+        palindrome: bool,
+    },
+
+    /// list the sequences which are the code for more than one word
+    #[structopt(name = "collisions")]
+    Collisions {
+        /// only sequences which are the code for at least this many words
+        #[structopt(long, default_value = "2")]
+        min: usize,
+
+        /// only sequences which are the code for exactly this many words, e.g. 13
+        #[structopt(long)]
+        count: Option<usize>,
+    },
+
+    /// find phrases of several words which encode like a word or phrase
+    #[structopt(name = "phrases")]
+    Phrases {
+        /// the word or phrase to match; without it, match every word in the list
+        target: Option<String>,
+
+        /// the most words in a phrase
+        #[structopt(long = "max-words", default_value = "2")]
+        max_words: usize,
+
+        /// leave words shorter than this out of phrases
+        #[structopt(long = "min-letters", default_value = "1")]
+        min_letters: usize,
+    },
+
+    /// find words and phrases whose codes are palindromes, or mirror other codes
+    #[structopt(name = "symmetry")]
+    Symmetry {
+        /// palindrome, reversal, or inversion (dots and dashes swapped); repeat for several, or omit for all
+        #[structopt(long, raw(number_of_values = "1"))]
+        kind: Vec<Symmetry>,
+
+        /// the fewest letters in a word or phrase
+        #[structopt(long = "min-length", default_value = "1")]
+        min_length: usize,
+
+        /// the most letters in a word or phrase
+        #[structopt(long = "max-length")]
+        max_length: Option<usize>,
+
+        /// the most words in a phrase
+        #[structopt(long = "max-words", default_value = "1")]
+        max_words: usize,
+
+        /// only the best this many results
+        #[structopt(long)]
+        top: Option<usize>,
+    },
+
+    /// search the encodings with a suffix array: which words contain each
+    /// pattern, the longest runs of each symbol, and the most frequent sequences
+    #[structopt(name = "substring", raw(setting = "AppSettings::AllowLeadingHyphen"))]
+    Substring {
+        /// sequences to look for; without any, or any other report, read them from stdin, one per line
+        patterns: Vec<String>,
+
+        /// report the longest run of dots and of dashes
+        #[structopt(long)]
+        runs: bool,
+
+        /// report the most frequent sequences of this length
+        #[structopt(long)]
+        kgrams: Option<usize>,
+
+        /// how many of the most frequent sequences to report
+        #[structopt(long, default_value = "10")]
+        top: usize,
+    },
+
+    /// list the sequences which appear in the encoding of no word
+    #[structopt(name = "absent")]
+    Absent {
+        /// length of the sequences
+        #[structopt(long, default_value = "13")]
+        len: usize,
+    },
+}
 
-fn main() -> Rv {
-    let opts = Opts::from_args();
+/// Limits and instrumentation for smalpha searches.
+#[derive(Debug, StructOpt)]
+struct Limits {
+    /// give up on each search after visiting this many nodes
+    #[structopt(long = "node-budget")]
+    node_budget: Option<u64>,
 
-    if let Some(s) = opts.input {
-        println!("{}", smorse(&s));
-    }
+    /// give up on each search after this many seconds
+    #[structopt(long)]
+    timeout: Option<f64>,
 
-    if let Some(wl_path) = opts.word_list {
-        if opts.bonus_1_1 {
-            bonus_1_1(&wl_path)?;
+    /// report statistics about each search
+    #[structopt(long)]
+    stats: bool,
+}
+
+impl Limits {
+    /// the limits for a single search, starting now
+    fn search_options(&self) -> SearchOptions {
+        let options = SearchOptions {
+            node_budget: self.node_budget,
+            stats: self.stats,
+            ..SearchOptions::default()
+        };
+        match self.timeout {
+            Some(secs) => options.with_timeout(Duration::from_secs_f64(secs)),
+            None => options,
         }
-        if opts.bonus_1_2 {
-            bonus_1_2(&wl_path)?;
+    }
+}
+
+#[derive(Debug, StructOpt)]
+enum SmalphaCommand {
+    /// find the first permutation which produces CODE
+    #[structopt(name = "solve", raw(setting = "AppSettings::AllowLeadingHyphen"))]
+    Solve {
+        code: String,
+
+        #[structopt(flatten)]
+        limits: Limits,
+    },
+
+    /// count every permutation which produces CODE
+    #[structopt(name = "count", raw(setting = "AppSettings::AllowLeadingHyphen"))]
+    Count {
+        code: String,
+
+        #[structopt(flatten)]
+        limits: Limits,
+    },
+
+    /// solve each line of a file, in parallel
+    #[structopt(name = "batch")]
+    Batch {
+        /// one code per line, which may be compressed, or - for stdin
+        #[structopt(parse(from_os_str))]
+        source: Source,
+
+        /// which solutions to find for each line: first, all, or count
+        #[structopt(long, default_value = "first")]
+        solutions: SolutionMode,
+
+        /// text, jsonl, or csv; defaults to jsonl when the format is json
+        #[structopt(long = "output-format")]
+        output_format: Option<OutputFormat>,
+
+        #[structopt(flatten)]
+        limits: Limits,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+enum SearchCommand {
+    /// find the lexicographically minimal input which produces exactly one alphabet
+    #[structopt(name = "minimal-unique")]
+    MinimalUnique {
+        /// resume from this point of an interrupted search
+        start: Option<i128>,
+    },
+}
+
+type Rv = Result<(), Box<dyn Error>>;
+
+fn main() {
+    if let Err(err) = run(Opts::from_args()) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run(opts: Opts) -> Rv {
+    let format = opts.format;
+
+    match opts.command {
+        Command::Encode { spaced, text } => encode(format, spaced, text.as_deref()),
+        Command::Decode { code } => decode(format, code.as_deref()),
+        Command::Wordlist(opts) => {
+            let (words, report) = opts.words()?;
+            let normalizer = opts.normalizer();
+            match opts.command {
+                WordlistCommand::Stats => wordlist_stats(format, &words, &report),
+                WordlistCommand::Query {
+                    code,
+                    contains,
+                    letters,
+                    balanced,
+                    palindrome,
+                } => wordlist_query(
+                    format,
+                    &words,
+                    &Query {
+                        code,
+                        contains,
+                        letters,
+                        balanced,
+                        palindrome,
+                    },
+                ),
+                WordlistCommand::Collisions { min, count } => {
+                    wordlist_collisions(format, &words, min, count)
+                }
+                WordlistCommand::Phrases {
+                    target,
+                    max_words,
+                    min_letters,
+                } => {
+                    let target = match target {
+                        Some(target) => Some(normalizer.encode_phrase(&target)?),
+                        None => None,
+                    };
+                    wordlist_phrases(format, &words, target, max_words, min_letters)
+                }
+                WordlistCommand::Symmetry {
+                    kind,
+                    min_length,
+                    max_length,
+                    max_words,
+                    top,
+                } => {
+                    let options = SymmetryOptions {
+                        kinds: if kind.is_empty() {
+                            Symmetry::ALL.to_vec()
+                        } else {
+                            kind
+                        },
+                        min_letters: min_length,
+                        max_letters: max_length,
+                        max_words,
+                        top,
+                    };
+                    wordlist_symmetry(format, &words, &options)
+                }
+                WordlistCommand::Substring {
+                    patterns,
+                    runs,
+                    kgrams,
+                    top,
+                } => wordlist_substring(format, &words, &patterns, runs, kgrams, top),
+                WordlistCommand::Absent { len } => wordlist_absent(format, &words, len),
+            }
         }
-        if opts.bonus_1_3 {
-            bonus_1_3(&wl_path)?;
+        Command::Smalpha(SmalphaCommand::Solve { code, limits }) => {
+            smalpha_solve(format, code.trim(), &limits)
         }
-        if opts.bonus_1_4 {
-            bonus_1_4(&wl_path)?;
+        Command::Smalpha(SmalphaCommand::Count { code, limits }) => {
+            smalpha_count(format, code.trim(), &limits)
         }
-        if opts.bonus_1_5 {
-            bonus_1_5(&wl_path)?;
+        Command::Smalpha(SmalphaCommand::Batch {
+            source,
+            solutions,
+            output_format,
+            limits,
+        }) => {
+            let output_format = output_format.unwrap_or(match format {
+                Format::Text => OutputFormat::Text,
+                Format::Json => OutputFormat::JsonLines,
+            });
+            let options = BatchOptions {
+                mode: solutions,
+                node_budget: limits.node_budget,
+                timeout: limits.timeout.map(Duration::from_secs_f64),
+                stats: limits.stats,
+                ..BatchOptions::default()
+            };
+            let inputs = Lines::open(source)?.collect::<Result<Vec<_>, _>>()?;
+            let results = solve_batch(&inputs, &options);
+            let stdout = io::stdout();
+            write_results(&mut stdout.lock(), &results, output_format)?;
+            Ok(())
         }
-        if !(opts.bonus_1_1 || opts.bonus_1_2 || opts.bonus_1_3 || opts.bonus_1_4 || opts.bonus_1_5)
-        {
-            let mut counts: Counter<u8> = Counter::new();
-            for word in get_words(&wl_path)? {
-                counts += smorse(&word).as_bytes().iter().cloned();
-            }
-            println!("Total counts:");
-            for (b, c) in counts.iter() {
-                println!(" {}: {}", *b as char, c);
-            }
+        Command::Search(SearchCommand::MinimalUnique { start }) => {
+            minimal_unique(format, start);
+            Ok(())
         }
-    } else {
-        if opts.bonus_1_1 || opts.bonus_1_2 || opts.bonus_1_3 || opts.bonus_1_4 || opts.bonus_1_5 {
-            eprintln!("bonus challenges require wordlist");
+    }
+}
+
+impl WordlistOpts {
+    fn normalizer(&self) -> Normalizer {
+        Normalizer {
+            fold_case: !self.keep_case,
+            accents: self.accents,
+            punctuation: self.punctuation,
         }
     }
 
-    if let Some(s) = opts.smalpha {
-        let expect = smorse(&(b'a'..=b'z').map(char::from).collect::<String>())
-            .chars()
-            .collect::<Counter<_>>();
-        let got = s.trim().chars().collect::<Counter<_>>();
-        if expect != got {
-            eprintln!("Bad input for smalpha: require:\n{:#?}", expect);
+    /// every word of every list, normalized and encoded, without duplicates
+    fn words(&self) -> Result<(Vec<Encoded>, NormalizeReport), Box<dyn Error>> {
+        let mut words = Words::new(self.word_list.iter().cloned()).strict(!self.lenient);
+        let raw = words.by_ref().collect::<Result<Vec<_>, _>>()?;
+        if words.skipped() > 0 {
+            eprintln!("skipped {} lines which are not UTF-8", words.skipped());
         }
-        match smalpha(&s) {
-            None => println!("no permutation found for this alphabet"),
-            Some(s) => println!("{}", s),
+        let (words, report) = self.normalizer().encode_all(raw);
+        if report.unchanged != words.len() {
+            eprintln!("normalized word list: {}", report);
         }
+        Ok((words, report))
     }
+}
 
-    if let Some(path) = opts.smorse_file {
-        for input in get_words(&path)? {
-            println!("{} -> {:?}", input, smalpha(&input));
-        }
+fn encode_line(spaced: bool, text: &str) -> String {
+    if spaced {
+        encode_spaced(text)
+    } else {
+        smorse(text)
     }
+}
 
-    if let Some(start) = opts.bonus_2_2 {
-        bonus_2_2(start);
+fn encode(format: Format, spaced: bool, text: Option<&str>) -> Rv {
+    if let Some(text) = text {
+        let code = encode_line(spaced, text);
+        format.print(&code, json!({ "text": text, "code": code }));
+        return Ok(());
     }
 
+    let stdin = io::stdin();
+    if format == Format::Text {
+        let stdout = io::stdout();
+        let mut encoder = if spaced {
+            MorseEncoder::new(stdout.lock())
+        } else {
+            MorseEncoder::squashed(stdout.lock())
+        };
+        io::copy(&mut stdin.lock(), &mut encoder)?;
+        encoder.flush()?;
+        return Ok(());
+    }
+    // each line is one record, so only text output streams lines of any length
+    for line in stdin.lock().lines() {
+        let line = line?;
+        let code = encode_line(spaced, &line);
+        format.print(&code, json!({ "text": line, "code": code }));
+    }
     Ok(())
 }
 
-fn get_words(wl_path: &Path) -> Result<Box<impl Iterator<Item = String>>, Box<dyn Error>> {
-    let wordlist = File::open(wl_path)?;
-    let reader = BufReader::new(wordlist);
-    Ok(Box::new(reader.lines().filter_map(|r| r.ok())))
-}
+fn decode(format: Format, code: Option<&str>) -> Rv {
+    if let Some(code) = code {
+        let text = decode_spaced(code)?;
+        format.print(&text, json!({ "code": code, "text": text }));
+        return Ok(());
+    }
 
-/// find the only sequence that's the code for 13 different words
-fn bonus_1_1(wl_path: &Path) -> Rv {
-    let counts = get_words(wl_path)?
-        .map(|word| smorse(&word))
-        .collect::<Counter<_>>();
-    for (seq, count) in counts.iter() {
-        if *count == 13 {
-            println!("Sequence encoding 13 words: {}", seq);
-            break;
-        }
+    let stdin = io::stdin();
+    if format == Format::Text {
+        let stdout = io::stdout();
+        io::copy(&mut MorseDecoder::new(stdin.lock()), &mut stdout.lock())?;
+        return Ok(());
     }
+    // each line is one record, so only text output streams lines of any length
+    for line in stdin.lock().lines() {
+        let line = line?;
+        let text = decode_spaced(&line)?;
+        format.print(&text, json!({ "code": line, "text": text }));
+    }
+    Ok(())
+}
 
+/// count the dots and dashes in the encodings of every word
+fn wordlist_stats(format: Format, words: &[Encoded], report: &NormalizeReport) -> Rv {
+    let (mut dots, mut dashes) = (0_usize, 0_usize);
+    for Encoded { code, .. } in words {
+        let word_dots = code.bytes().filter(|&b| b == b'.').count();
+        dots += word_dots;
+        dashes += code.len() - word_dots;
+    }
+    format.print(
+        format_args!(
+            "{} words ({})\nTotal counts:\n .: {}\n -: {}",
+            words.len(),
+            report,
+            dots,
+            dashes
+        ),
+        json!({
+            "words": words.len(),
+            "normalization": report,
+            "dots": dots,
+            "dashes": dashes,
+        }),
+    );
     Ok(())
 }
 
-/// find the only word that has 15 dashes in a row
-fn bonus_1_2(wl_path: &Path) -> Result<(), Box<dyn Error>> {
-    let needle = b"---------------";
-    'outer: for word in get_words(wl_path)? {
-        let seq = smorse(&word);
-        let hay = seq.as_bytes();
-        for w in hay.windows(15) {
-            if w == needle {
-                println!("{} encodes as {} which has 15 dashes in a row", word, seq);
-                break 'outer;
+/// Filters for `wordlist query`; a word must pass all of them.
+struct Query {
+    code: Option<String>,
+    contains: Option<String>,
+    letters: Option<usize>,
+    balanced: bool,
+    palindrome: bool,
+}
+
+impl Query {
+    fn matches(&self, word: &str, seq: &str) -> bool {
+        if let Some(letters) = self.letters {
+            if word.chars().count() != letters {
+                return false;
+            }
+        }
+        if let Some(code) = &self.code {
+            if seq != code {
+                return false;
+            }
+        }
+        if let Some(needle) = &self.contains {
+            if !seq.contains(needle.as_str()) {
+                return false;
+            }
+        }
+        if self.balanced {
+            let dots = seq.bytes().filter(|&b| b == b'.').count();
+            if seq.is_empty() || dots * 2 != seq.len() {
+                return false;
+            }
+        }
+        if self.palindrome {
+            let seqb = seq.as_bytes();
+            if !seqb.iter().eq(seqb.iter().rev()) {
+                return false;
             }
         }
+        true
     }
+}
 
+/// list the words whose encodings pass every filter of the query
+fn wordlist_query(format: Format, words: &[Encoded], query: &Query) -> Rv {
+    for Encoded { word, code: seq } in words {
+        if query.matches(word, seq) {
+            format.print(
+                format_args!("{} encodes as {}", word, seq),
+                json!({ "word": word, "code": seq }),
+            );
+        }
+    }
     Ok(())
 }
 
-/// find all 21-letter words with the same number of dots and dashes
-fn bonus_1_3(wl_path: &Path) -> Rv {
-    for word in get_words(wl_path)?.filter(|word| word.len() == 21) {
-        let seq = smorse(&word);
-        let counts = seq.as_bytes().iter().collect::<Counter<_>>();
-        if counts.get(&b'-').is_some() && counts.get(&b'-') == counts.get(&b'.') {
-            println!(
-                "{} encodes as {} which has {} each dots and dashes",
-                word,
-                seq,
-                counts.get(&b'-').unwrap()
+/// list the sequences which are the code for several words
+fn wordlist_collisions(format: Format, words: &[Encoded], min: usize, count: Option<usize>) -> Rv {
+    let mut codes: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for Encoded { word, code } in words {
+        codes.entry(code).or_default().push(word);
+    }
+    for (seq, words) in codes {
+        let wanted = match count {
+            Some(count) => words.len() == count,
+            None => words.len() >= min,
+        };
+        if wanted {
+            format.print(
+                format_args!("{} encodes {} words: {}", seq, words.len(), words.join(" ")),
+                json!({ "code": seq, "words": words }),
             );
         }
     }
-
     Ok(())
 }
 
-/// find the only 13-letter word that encodes to a palindrome
-fn bonus_1_4(wl_path: &Path) -> Rv {
-    for word in get_words(wl_path)?.filter(|word| word.len() == 13) {
-        let seq = smorse(&word);
-        let seqb = seq.as_bytes();
-        if seqb.iter().zip(seqb.iter().rev()).all(|(a, b)| a == b) {
-            println!("{} encodes as {} which is a palindrome", word, seq);
+/// list the phrases which encode like `target`, or like each word if there is no target
+fn wordlist_phrases(
+    format: Format,
+    words: &[Encoded],
+    target: Option<Encoded>,
+    max_words: usize,
+    min_letters: usize,
+) -> Rv {
+    if max_words < 2 {
+        return Err("a phrase has at least two words".into());
+    }
+    let parts = words
+        .iter()
+        .filter(|e| e.word.chars().count() >= min_letters)
+        .cloned()
+        .collect::<Vec<_>>();
+    let index = CodeIndex::new(&parts);
+    let targets = match &target {
+        Some(target) => std::slice::from_ref(target),
+        None => words,
+    };
+    let found = targets
+        .par_iter()
+        .map(|target| (target, index.phrases(&target.code, max_words)))
+        .collect::<Vec<_>>();
+    for (target, phrases) in found {
+        for phrase in phrases {
+            let phrase = phrase_words(&phrase);
+            if phrase.join(" ") == target.word {
+                continue;
+            }
+            format.print(
+                format_args!("{} encodes like {}", phrase.join(" "), target.word),
+                json!({ "target": target.word, "code": target.code, "phrase": phrase }),
+            );
         }
     }
+    Ok(())
+}
+
+fn phrase_words<'a>(phrase: &[&'a Encoded]) -> Vec<&'a str> {
+    phrase.iter().map(|e| e.word.as_str()).collect()
+}
 
+/// list the words and phrases with symmetric codes, best first
+fn wordlist_symmetry(format: Format, words: &[Encoded], options: &SymmetryOptions) -> Rv {
+    let index = CodeIndex::new(words);
+    let found = find_symmetries(&index, options);
+    for symmetric in &found {
+        let phrase = phrase_text(&symmetric.phrase);
+        let partner = phrase_text(&symmetric.partner);
+        let code = symmetric.code();
+        let text = match symmetric.kind {
+            Symmetry::Palindrome => format!("{} encodes as {} which is a palindrome", phrase, code),
+            Symmetry::Reversal => format!(
+                "{} encodes as {} which reversed is {}",
+                phrase, code, partner
+            ),
+            Symmetry::Inversion => format!(
+                "{} encodes as {} which inverted is {}",
+                phrase, code, partner
+            ),
+        };
+        format.print(
+            text,
+            json!({
+                "kind": symmetric.kind,
+                "phrase": phrase_words(&symmetric.phrase),
+                "code": code,
+                "partner": phrase_words(&symmetric.partner),
+            }),
+        );
+    }
     Ok(())
 }
 
-fn n2s(n: u16) -> String {
-    let mut out = String::with_capacity(16);
-    for i in (0_u16..16).rev() {
-        if n & (1 << i) > 0 {
-            out.push('-')
-        } else {
-            out.push('.')
+/// answer substring queries from a suffix array over every encoding
+fn wordlist_substring(
+    format: Format,
+    words: &[Encoded],
+    patterns: &[String],
+    runs: bool,
+    kgrams: Option<usize>,
+    top: usize,
+) -> Rv {
+    let index = SuffixIndex::new(words);
+    if runs {
+        for run in &index.longest_runs() {
+            let words = run
+                .words
+                .iter()
+                .map(|e| e.word.as_str())
+                .collect::<Vec<_>>();
+            format.print(
+                format_args!(
+                    "longest run of {}: {} in {}",
+                    run.symbol,
+                    run.len,
+                    words.join(" ")
+                ),
+                json!({ "symbol": run.symbol, "len": run.len, "words": words }),
+            );
         }
     }
-    out
+    if let Some(k) = kgrams {
+        for (gram, count) in index.kgrams(k, top) {
+            format.print(
+                format_args!("{} appears {} times", gram, count),
+                json!({ "code": gram, "count": count }),
+            );
+        }
+    }
+    let find = |pattern: &str| {
+        let found = index.words_containing(pattern);
+        let words = found.iter().map(|e| e.word.as_str()).collect::<Vec<_>>();
+        format.print(
+            format_args!(
+                "{} is in {} words: {}",
+                pattern,
+                words.len(),
+                words.join(" ")
+            ),
+            json!({ "pattern": pattern, "occurrences": index.occurrences(pattern), "words": words }),
+        );
+    };
+    if !patterns.is_empty() || runs || kgrams.is_some() {
+        patterns.iter().for_each(|pattern| find(pattern));
+        return Ok(());
+    }
+    // the index is built once, so stdin can be an interactive session
+    let stdin = io::stdin();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        let pattern = line.trim();
+        if !pattern.is_empty() {
+            find(pattern);
+        }
+    }
+    Ok(())
 }
 
-/// find all 13-char sequences which do not appear in the encoding of any word
-fn bonus_1_5(wl_path: &Path) -> Rv {
-    let word_sequences = get_words(wl_path)?
-        .map(|word| smorse(&word).into_bytes())
-        .collect::<HashSet<_>>();
-
-    println!("13-char sequences which appear in no words:");
-    for seq in (0_u16..(1 << 13)).map(n2s) {
-        let needle = &seq.as_bytes()[3..]; // final 13
-        if !word_sequences
-            .iter()
-            .any(|ws| ws.windows(13).any(|hay| needle == hay))
-        {
-            println!(" {}", &seq[3..]);
+/// The longest sequences `wordlist absent` will look for; there are `2^len` of them.
+const MAX_ABSENT_LEN: usize = 24;
+
+/// list the sequences of length `len` which appear in the encoding of no word
+fn wordlist_absent(format: Format, words: &[Encoded], len: usize) -> Rv {
+    if len == 0 || len > MAX_ABSENT_LEN {
+        return Err(format!("sequence length must be between 1 and {}", MAX_ABSENT_LEN).into());
+    }
+    // treating dots as 0 and dashes as 1, each sequence is a `len`-bit number
+    let mut seen = vec![false; 1 << len];
+    for Encoded { code, .. } in words {
+        for window in code.as_bytes().windows(len) {
+            let idx = window
+                .iter()
+                .fold(0_usize, |idx, &b| idx << 1 | (b == b'-') as usize);
+            seen[idx] = true;
         }
     }
+    for (idx, _) in seen.iter().enumerate().filter(|(_, seen)| !**seen) {
+        let seq = (0..len)
+            .rev()
+            .map(|bit| if idx & (1 << bit) != 0 { '-' } else { '.' })
+            .collect::<String>();
+        format.print(&seq, json!({ "code": seq }));
+    }
+    Ok(())
+}
+
+fn smalpha_solve(format: Format, code: &str, limits: &Limits) -> Rv {
+    check_alphabet_code(code)?;
+    let outcome = smalpha_with(code, &limits.search_options());
+    let mut text = match outcome.found {
+        Some(perm) => perm.to_string(),
+        None => "no permutation found for this alphabet".into(),
+    };
+    if outcome.status != SearchStatus::Finished {
+        text += &format!(" [{}]", outcome.status);
+    }
+    if let Some(stats) = &outcome.stats {
+        text += &format!("\n{}", stats);
+    }
+    format.print(
+        text,
+        json!({
+            "input": code,
+            "found": outcome.found,
+            "status": outcome.status,
+            "stats": outcome.stats,
+        }),
+    );
+    Ok(())
+}
 
+fn smalpha_count(format: Format, code: &str, limits: &Limits) -> Rv {
+    check_alphabet_code(code)?;
+    let mut search = smalpha_all_with(code, &limits.search_options());
+    let count = search.by_ref().count();
+    let status = search.status().unwrap_or(SearchStatus::Finished);
+    let stats = search.stats();
+    let mut text = format!("{} solutions", count);
+    if status != SearchStatus::Finished {
+        text += &format!(" [{}]", status);
+    }
+    if let Some(stats) = &stats {
+        text += &format!("\n{}", stats);
+    }
+    format.print(
+        text,
+        json!({ "input": code, "count": count, "status": status, "stats": stats }),
+    );
     Ok(())
 }
 
@@ -234,7 +837,7 @@ fn bonus_1_5(wl_path: &Path) -> Rv {
 /// Because of this restatement, we can sharply reduce the number of permutations
 /// we must consider, because there are efficient algorithms for generating
 /// integers containing exactly N `1` bits.
-fn bonus_2_2(start: Option<i128>) {
+fn minimal_unique(format: Format, start: Option<i128>) {
     // this is expected to be a long-running process, so we
     use rayon::iter::ParallelBridge;
     use rayon::prelude::ParallelIterator;
@@ -254,25 +857,35 @@ fn bonus_2_2(start: Option<i128>) {
         .inspect(|(idx, input)| {
             // emit some output every once in a while just to demonstrate activity
             if idx & 0xff_ffff == 0 {
-                println!("{}", input);
+                eprintln!("{}", input);
             }
         })
         .map_with(halt, |halt, (idx, input)| {
             // check the interrupt every 256 iterations
             if idx & 0xfff == 0 && halt.load(Ordering::SeqCst) {
-                println!();
-                println!("Checked {} inputs; continue with", idx);
-                println!("  smorse --bonus-2-2 {}", InputGenerator::i2n(&input));
+                eprintln!();
+                eprintln!("Checked {} inputs; continue with", idx);
+                eprintln!(
+                    "  smorse search minimal-unique {}",
+                    InputGenerator::i2n(&input)
+                );
                 None
             } else {
                 Some((idx, input))
             }
         })
         .while_some()
-        .find_first(|(_, input)| smalpha_all(&input).take(2).count() == 1)
+        .find_first(|(_, input)| smalpha_all(input).take(2).count() == 1)
     {
-        println!("{} => {}", input, smalpha(&input).unwrap());
+        let alphabet = smalpha(&input).unwrap();
+        format.print(
+            format_args!("{} => {}", input, alphabet),
+            json!({ "input": input, "alphabet": alphabet }),
+        );
     } else {
-        println!("no valid input was found");
+        format.print(
+            "no valid input was found",
+            json!({ "input": null, "alphabet": null }),
+        );
     }
 }
@@ -0,0 +1,354 @@
+use crate::encoding::{extended_code, letter_code, CH};
+use crate::smorse;
+use crate::words::Encoded;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+use unicode_normalization::UnicodeNormalization;
+
+/// What to do with letters outside `a..=z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Accents {
+    /// drop accents, so that `é` becomes `e`
+    Strip,
+    /// keep letters which have an extended morse code, such as `é`, and encode `ch` as one letter
+    Extended,
+}
+
+impl FromStr for Accents {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Accents, String> {
+        match s {
+            "strip" => Ok(Accents::Strip),
+            "extended" => Ok(Accents::Extended),
+            _ => Err(format!(
+                "unknown accent mode {:?}: expect strip or extended",
+                s
+            )),
+        }
+    }
+}
+
+/// What to do with anything which isn't a letter, such as apostrophes, hyphens, and digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Punctuation {
+    /// drop it from the word
+    Remove,
+    /// reject the whole word
+    Reject,
+}
+
+impl FromStr for Punctuation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Punctuation, String> {
+        match s {
+            "remove" => Ok(Punctuation::Remove),
+            "reject" => Ok(Punctuation::Reject),
+            _ => Err(format!(
+                "unknown punctuation mode {:?}: expect remove or reject",
+                s
+            )),
+        }
+    }
+}
+
+/// Why a word was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    /// it contains punctuation, and `Punctuation::Reject` is in effect
+    Punctuation(char),
+    /// it contains a letter which can't be encoded, even without its accents
+    Unencodable(char),
+    /// nothing is left once punctuation is removed
+    Empty,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rejection::Punctuation(c) => write!(f, "contains punctuation {:?}", c),
+            Rejection::Unencodable(c) => write!(f, "{:?} has no morse code", c),
+            Rejection::Empty => f.write_str("no letters"),
+        }
+    }
+}
+
+impl Error for Rejection {}
+
+/// Letters which don't decompose into a base letter and accents, spelled in `a..=z`.
+fn transliterate(c: char) -> Option<&'static str> {
+    match c {
+        'ß' => Some("ss"),
+        'æ' => Some("ae"),
+        'œ' => Some("oe"),
+        'ø' => Some("o"),
+        'đ' | 'ð' => Some("d"),
+        'ł' => Some("l"),
+        'þ' => Some("th"),
+        'ı' => Some("i"),
+        _ => None,
+    }
+}
+
+/// Turns words into strings of letters which `Normalizer::encode` can encode without loss.
+///
+/// `smorse` silently drops anything outside `a..=z`, so unnormalized words
+/// such as `Don't` or `café` get truncated codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Normalizer {
+    /// lowercase every letter; otherwise, capitals can't be encoded
+    pub fold_case: bool,
+    pub accents: Accents,
+    pub punctuation: Punctuation,
+}
+
+impl Default for Normalizer {
+    fn default() -> Normalizer {
+        Normalizer {
+            fold_case: true,
+            accents: Accents::Strip,
+            punctuation: Punctuation::Remove,
+        }
+    }
+}
+
+impl Normalizer {
+    fn encodable(&self, c: char) -> bool {
+        letter_code(c).is_some()
+            || (self.accents == Accents::Extended && extended_code(c).is_some())
+    }
+
+    /// Push the base letters of `c` onto `out`, or return `false` if it has none.
+    fn push_stripped(&self, c: char, out: &mut String) -> bool {
+        let mut ok = true;
+        decompose_canonical(c, |d| {
+            if is_combining_mark(d) {
+                return;
+            }
+            if self.encodable(d) {
+                out.push(d);
+            } else if let Some(s) = transliterate(d) {
+                out.push_str(s);
+            } else {
+                ok = false;
+            }
+        });
+        ok
+    }
+
+    fn push_char(&self, c: char, out: &mut String) -> Result<(), Rejection> {
+        if self.encodable(c) {
+            out.push(c);
+        } else if c.is_alphabetic() || is_combining_mark(c) {
+            if !self.push_stripped(c, out) {
+                return Err(Rejection::Unencodable(c));
+            }
+        } else if self.punctuation == Punctuation::Reject {
+            return Err(Rejection::Punctuation(c));
+        }
+        Ok(())
+    }
+
+    /// Normalize one word, borrowing it if nothing needed to change.
+    pub fn normalize<'a>(&self, word: &'a str) -> Result<Cow<'a, str>, Rejection> {
+        let mut out = String::with_capacity(word.len());
+        for c in word.nfc() {
+            if self.fold_case {
+                for c in c.to_lowercase() {
+                    self.push_char(c, &mut out)?;
+                }
+            } else {
+                self.push_char(c, &mut out)?;
+            }
+        }
+        if out.is_empty() {
+            Err(Rejection::Empty)
+        } else if out == word {
+            Ok(Cow::Borrowed(word))
+        } else {
+            Ok(Cow::Owned(out))
+        }
+    }
+
+    /// The squashed code of a normalized word.
+    ///
+    /// With `Accents::Extended`, this uses the extended codes, including `ch`.
+    pub fn encode(&self, normalized: &str) -> String {
+        if self.accents == Accents::Strip {
+            return smorse(normalized);
+        }
+        let mut out = String::new();
+        let mut chars = normalized.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == 'c' && chars.peek() == Some(&'h') {
+                chars.next();
+                out.push_str(CH);
+            } else if let Some(code) = letter_code(c).or_else(|| extended_code(c)) {
+                out.push_str(code);
+            }
+        }
+        out
+    }
+
+    /// Normalize and encode each word of a phrase, joining them with spaces.
+    pub fn encode_phrase(&self, phrase: &str) -> Result<Encoded, Rejection> {
+        let mut words = Vec::new();
+        for word in phrase.split_whitespace() {
+            words.push(self.normalize(word)?);
+        }
+        if words.is_empty() {
+            return Err(Rejection::Empty);
+        }
+        let code = words.iter().map(|word| self.encode(word)).collect();
+        Ok(Encoded {
+            word: words.join(" "),
+            code,
+        })
+    }
+
+    /// Normalize and encode every word, keeping only the first of any which normalize alike.
+    pub fn encode_all<I>(&self, words: I) -> (Vec<Encoded>, NormalizeReport)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut report = NormalizeReport::default();
+        let mut seen = HashSet::new();
+        let mut out = Vec::new();
+        for word in words {
+            let word = match self.normalize(word.as_ref()) {
+                Ok(Cow::Borrowed(word)) => {
+                    report.unchanged += 1;
+                    word.to_string()
+                }
+                Ok(Cow::Owned(word)) => {
+                    report.changed += 1;
+                    word
+                }
+                Err(_) => {
+                    report.rejected += 1;
+                    continue;
+                }
+            };
+            if !seen.insert(word.clone()) {
+                report.merged += 1;
+                continue;
+            }
+            let code = self.encode(&word);
+            out.push(Encoded { word, code });
+        }
+        (out, report)
+    }
+}
+
+/// How many words normalization changed or rejected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct NormalizeReport {
+    pub unchanged: usize,
+    pub changed: usize,
+    pub rejected: usize,
+    /// words which, once normalized, duplicated an earlier word and were dropped
+    pub merged: usize,
+}
+
+impl fmt::Display for NormalizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} unchanged, {} changed, {} rejected, {} merged into earlier words",
+            self.unchanged, self.changed, self.rejected, self.merged
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn strict() -> Normalizer {
+        Normalizer {
+            fold_case: false,
+            accents: Accents::Strip,
+            punctuation: Punctuation::Reject,
+        }
+    }
+
+    fn extended() -> Normalizer {
+        Normalizer {
+            accents: Accents::Extended,
+            ..Normalizer::default()
+        }
+    }
+
+    #[test]
+    fn test_default() {
+        let n = Normalizer::default();
+        assert_eq!(n.normalize("sos"), Ok(Cow::Borrowed("sos")));
+        assert_eq!(n.normalize("Don't").unwrap(), "dont");
+        assert_eq!(n.normalize("Café").unwrap(), "cafe");
+        assert_eq!(n.normalize("cafe\u{301}").unwrap(), "cafe");
+        assert_eq!(n.normalize("Straße").unwrap(), "strasse");
+        assert_eq!(n.normalize("'").unwrap_err(), Rejection::Empty);
+        assert_eq!(n.normalize("мир").unwrap_err(), Rejection::Unencodable('м'));
+    }
+
+    #[test]
+    fn test_strict() {
+        let n = strict();
+        assert_eq!(n.normalize("Don").unwrap_err(), Rejection::Unencodable('D'));
+        assert_eq!(
+            n.normalize("don't").unwrap_err(),
+            Rejection::Punctuation('\'')
+        );
+        assert_eq!(n.normalize("café").unwrap(), "cafe");
+    }
+
+    #[test]
+    fn test_extended() {
+        let n = extended();
+        assert_eq!(n.normalize("Ñandú").unwrap(), "ñandu");
+        assert_eq!(n.normalize("Käse").unwrap(), "käse");
+        assert_eq!(n.encode("ñ"), "--.--");
+        assert_eq!(n.encode("ch"), CH);
+        assert_eq!(n.encode("käse"), "-.-.-.-....");
+        assert_eq!(Normalizer::default().encode("ch"), "-.-.....");
+    }
+
+    #[test]
+    fn test_encode_phrase() {
+        let n = Normalizer::default();
+        let phrase = n.encode_phrase(" Pro  Grammer ").unwrap();
+        assert_eq!(phrase.word, "pro grammer");
+        assert_eq!(phrase.code, smorse("programmer"));
+        assert_eq!(n.encode_phrase("  ").unwrap_err(), Rejection::Empty);
+    }
+
+    #[test]
+    fn test_encode_all() {
+        let (words, report) =
+            Normalizer::default().encode_all(&["sos", "SOS", "Don't", "don't", "'", "café"]);
+        assert_eq!(
+            words,
+            vec![
+                Encoded::new("sos"),
+                Encoded::new("dont"),
+                Encoded::new("cafe"),
+            ]
+        );
+        assert_eq!(
+            report,
+            NormalizeReport {
+                unchanged: 1,
+                changed: 4,
+                rejected: 1,
+                merged: 2,
+            }
+        );
+    }
+}
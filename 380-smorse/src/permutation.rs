@@ -0,0 +1,276 @@
+use crate::smorse;
+use serde::{Serialize, Serializer};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A permutation of the alphabet.
+///
+/// Index `i` holds the letter in position `i`; letters are stored as indices
+/// in `0..26`, so the permutation can equally be seen as a bijection on `0..26`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Permutation([u8; 26]);
+
+/// Whether a permutation is made of an even or odd number of transpositions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Parity {
+    Even,
+    Odd,
+}
+
+fn letter(idx: u8) -> char {
+    (idx + b'a') as char
+}
+
+fn letter_idx(c: char) -> Option<u8> {
+    match c {
+        'a'..='z' => Some(c as u8 - b'a'),
+        _ => None,
+    }
+}
+
+impl Permutation {
+    /// The alphabet in its usual order.
+    pub fn identity() -> Permutation {
+        let mut perm = [0; 26];
+        for (idx, p) in perm.iter_mut().enumerate() {
+            *p = idx as u8;
+        }
+        Permutation(perm)
+    }
+
+    /// Build a permutation from letter indices, which must already be a permutation of `0..26`.
+    pub(crate) fn from_indices(perm: [u8; 26]) -> Permutation {
+        debug_assert_eq!(perm.iter().fold(0_u32, |acc, &p| acc | 1 << p), 0x03ff_ffff);
+        Permutation(perm)
+    }
+
+    /// The letter in position `idx`.
+    ///
+    /// Panics if `idx >= 26`.
+    pub fn letter(&self, idx: usize) -> char {
+        letter(self.0[idx])
+    }
+
+    /// The position of `c`, or `None` if it is not a lowercase letter.
+    pub fn index_of(&self, c: char) -> Option<usize> {
+        let target = letter_idx(c)?;
+        self.0.iter().position(|&p| p == target)
+    }
+
+    /// The letters in order, as indices in `0..26`.
+    pub fn as_indices(&self) -> &[u8; 26] {
+        &self.0
+    }
+
+    /// The permutation which undoes this one: `p.compose(&p.inverse())` is the identity.
+    pub fn inverse(&self) -> Permutation {
+        let mut inv = [0; 26];
+        for (idx, &p) in self.0.iter().enumerate() {
+            inv[p as usize] = idx as u8;
+        }
+        Permutation(inv)
+    }
+
+    /// Apply `other` first, then `self`: position `i` of the result is `self[other[i]]`.
+    pub fn compose(&self, other: &Permutation) -> Permutation {
+        let mut perm = [0; 26];
+        for (p, &o) in perm.iter_mut().zip(other.0.iter()) {
+            *p = self.0[o as usize];
+        }
+        Permutation(perm)
+    }
+
+    /// The cycles of this permutation, omitting fixed points.
+    ///
+    /// Within a cycle, the letter in the position named by each letter is the next
+    /// letter of the cycle. Each cycle starts at its lowest letter, and the cycles
+    /// are ordered by their first letters.
+    pub fn cycles(&self) -> Vec<Vec<char>> {
+        let mut seen = 0_u32;
+        let mut cycles = Vec::new();
+        for start in 0..26_u8 {
+            if seen & (1 << start) != 0 || self.0[start as usize] == start {
+                continue;
+            }
+            let mut cycle = Vec::new();
+            let mut idx = start;
+            while seen & (1 << idx) == 0 {
+                seen |= 1 << idx;
+                cycle.push(letter(idx));
+                idx = self.0[idx as usize];
+            }
+            cycles.push(cycle);
+        }
+        cycles
+    }
+
+    pub fn parity(&self) -> Parity {
+        // a cycle of length n is the product of n - 1 transpositions
+        let transpositions: usize = self.cycles().iter().map(|c| c.len() - 1).sum();
+        if transpositions.is_multiple_of(2) {
+            Parity::Even
+        } else {
+            Parity::Odd
+        }
+    }
+
+    /// The squashed morse code of the letters in order.
+    pub fn code(&self) -> String {
+        smorse(&self.to_string())
+    }
+}
+
+impl fmt::Display for Permutation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &p in self.0.iter() {
+            write!(f, "{}", letter(p))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Permutation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Permutation({:?})", self.to_string())
+    }
+}
+
+impl Serialize for Permutation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Why a string could not be parsed as a `Permutation`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParsePermutationError {
+    /// a permutation contains exactly 26 letters
+    WrongLength(usize),
+    /// only lowercase ascii letters are allowed
+    NotALetter(char),
+    /// each letter must appear exactly once
+    Repeated(char),
+}
+
+impl fmt::Display for ParsePermutationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParsePermutationError::WrongLength(len) => {
+                write!(f, "expected 26 letters, found {}", len)
+            }
+            ParsePermutationError::NotALetter(c) => {
+                write!(f, "{:?} is not a lowercase letter", c)
+            }
+            ParsePermutationError::Repeated(c) => write!(f, "{:?} appears more than once", c),
+        }
+    }
+}
+
+impl Error for ParsePermutationError {}
+
+impl FromStr for Permutation {
+    type Err = ParsePermutationError;
+
+    fn from_str(s: &str) -> Result<Permutation, ParsePermutationError> {
+        let len = s.chars().count();
+        if len != 26 {
+            return Err(ParsePermutationError::WrongLength(len));
+        }
+        let mut perm = [0; 26];
+        let mut seen = 0_u32;
+        for (p, c) in perm.iter_mut().zip(s.chars()) {
+            let idx = letter_idx(c).ok_or(ParsePermutationError::NotALetter(c))?;
+            if seen & (1 << idx) != 0 {
+                return Err(ParsePermutationError::Repeated(c));
+            }
+            seen |= 1 << idx;
+            *p = idx;
+        }
+        Ok(Permutation(perm))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PERM: &str = "wirnbfzehatqlojpgcvusyxkmd";
+
+    #[test]
+    fn test_round_trip() {
+        let perm: Permutation = PERM.parse().unwrap();
+        assert_eq!(perm.to_string(), PERM);
+        assert_eq!(
+            Permutation::identity().to_string(),
+            "abcdefghijklmnopqrstuvwxyz"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "abc".parse::<Permutation>(),
+            Err(ParsePermutationError::WrongLength(3))
+        );
+        assert_eq!(
+            "Abcdefghijklmnopqrstuvwxyz".parse::<Permutation>(),
+            Err(ParsePermutationError::NotALetter('A'))
+        );
+        assert_eq!(
+            "abcdefghijklmnopqrstuvwxya".parse::<Permutation>(),
+            Err(ParsePermutationError::Repeated('a'))
+        );
+    }
+
+    #[test]
+    fn test_lookup() {
+        let perm: Permutation = PERM.parse().unwrap();
+        for (idx, c) in PERM.chars().enumerate() {
+            assert_eq!(perm.letter(idx), c);
+            assert_eq!(perm.index_of(c), Some(idx));
+        }
+        assert_eq!(perm.index_of('!'), None);
+    }
+
+    #[test]
+    fn test_inverse_compose() {
+        let perm: Permutation = PERM.parse().unwrap();
+        let id = Permutation::identity();
+        assert_eq!(perm.compose(&perm.inverse()), id);
+        assert_eq!(perm.inverse().compose(&perm), id);
+        assert_eq!(perm.compose(&id), perm);
+        assert_eq!(id.compose(&perm), perm);
+        assert_eq!(perm.inverse().inverse(), perm);
+    }
+
+    #[test]
+    fn test_cycles_parity() {
+        let id = Permutation::identity();
+        assert!(id.cycles().is_empty());
+        assert_eq!(id.parity(), Parity::Even);
+
+        let swap: Permutation = "bacdefghijklmnopqrstuvwxyz".parse().unwrap();
+        assert_eq!(swap.cycles(), vec![vec!['a', 'b']]);
+        assert_eq!(swap.parity(), Parity::Odd);
+
+        let rot: Permutation = "bcadefghijklmnopqrstuvwxyz".parse().unwrap();
+        assert_eq!(rot.cycles(), vec![vec!['a', 'b', 'c']]);
+        assert_eq!(rot.parity(), Parity::Even);
+
+        let perm: Permutation = PERM.parse().unwrap();
+        let covered: usize = perm.cycles().iter().map(Vec::len).sum();
+        let fixed = (0..26)
+            .filter(|&i| perm.as_indices()[i] as usize == i)
+            .count();
+        assert_eq!(covered + fixed, 26);
+    }
+
+    #[test]
+    fn test_code() {
+        assert_eq!(
+            Permutation::identity().code(),
+            smorse("abcdefghijklmnopqrstuvwxyz")
+        );
+    }
+}
@@ -0,0 +1,370 @@
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often, in nodes, the search looks at the clock and the cancellation token.
+///
+/// Both are much more expensive than visiting a node, so we don't check them every time.
+const CHECK_INTERVAL: u64 = 0x400;
+
+/// A flag which can be shared between threads to stop a running search.
+///
+/// Clones share the same flag, so cancelling any clone cancels them all.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Limits on how much work an alphabet search may do before giving up.
+///
+/// The default is unlimited.
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// stop after visiting this many nodes of the search tree
+    pub node_budget: Option<u64>,
+    /// stop once this instant has passed
+    pub deadline: Option<Instant>,
+    /// stop as soon as this token is cancelled
+    pub cancel: Option<CancelToken>,
+    /// collect `SearchStats` while searching
+    pub stats: bool,
+}
+
+impl SearchOptions {
+    /// Set the deadline to `timeout` from now.
+    pub fn with_timeout(mut self, timeout: Duration) -> SearchOptions {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+}
+
+/// Why a search stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchStatus {
+    /// the whole search space was explored
+    Finished,
+    /// the node budget or deadline ran out; any results are partial
+    BudgetExhausted,
+    /// the cancellation token was triggered; any results are partial
+    Cancelled,
+}
+
+impl SearchStatus {
+    /// A short machine-readable name, as used when serializing.
+    pub fn name(self) -> &'static str {
+        match self {
+            SearchStatus::Finished => "finished",
+            SearchStatus::BudgetExhausted => "budget_exhausted",
+            SearchStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl fmt::Display for SearchStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SearchStatus::Finished => write!(f, "finished"),
+            SearchStatus::BudgetExhausted => write!(f, "budget exhausted (partial results)"),
+            SearchStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// The result of a search, together with the reason it stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchOutcome<T> {
+    pub found: T,
+    pub status: SearchStatus,
+    /// statistics about the search, if they were requested
+    pub stats: Option<SearchStats>,
+}
+
+pub(crate) fn serialize_secs<S: Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_f64(d.as_secs_f64())
+}
+
+fn serialize_opt_secs<S: Serializer>(d: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+    match d {
+        Some(d) => serialize_secs(d, s),
+        None => s.serialize_none(),
+    }
+}
+
+/// Statistics gathered during an alphabet search.
+///
+/// Depths and letters are both indices in `0..26`. Times count only the time
+/// actually spent searching, not time between calls to a lazy search.
+/// They serialize as fractional seconds.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SearchStats {
+    /// nodes of the search tree visited
+    pub nodes: u64,
+    /// solutions found
+    pub solutions: u64,
+    /// how many letter choices at each depth led nowhere
+    pub backtracks: [u64; 26],
+    /// how many times each letter was chosen
+    pub letter_tries: [u64; 26],
+    /// how many times choosing each letter led to a solution
+    pub letter_successes: [u64; 26],
+    #[serde(serialize_with = "serialize_opt_secs")]
+    pub time_to_first_solution: Option<Duration>,
+    #[serde(serialize_with = "serialize_secs")]
+    pub total_time: Duration,
+}
+
+impl SearchStats {
+    /// The fraction of the times `letter` was chosen which led to a solution.
+    pub fn success_rate(&self, letter: u8) -> Option<f64> {
+        let tries = self.letter_tries[letter as usize];
+        if tries == 0 {
+            None
+        } else {
+            Some(self.letter_successes[letter as usize] as f64 / tries as f64)
+        }
+    }
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "nodes visited: {}", self.nodes)?;
+        writeln!(f, "solutions: {}", self.solutions)?;
+        match self.time_to_first_solution {
+            Some(t) => writeln!(f, "time to first solution: {:?}", t)?,
+            None => writeln!(f, "time to first solution: n/a")?,
+        }
+        writeln!(f, "total time: {:?}", self.total_time)?;
+        writeln!(f, "backtracks per depth:")?;
+        for (depth, count) in self.backtracks.iter().enumerate() {
+            writeln!(f, " {:2}: {}", depth, count)?;
+        }
+        write!(f, "letter success rates:")?;
+        for letter in 0..26 {
+            writeln!(f)?;
+            write!(
+                f,
+                " {}: {}/{}",
+                (letter + b'a') as char,
+                self.letter_successes[letter as usize],
+                self.letter_tries[letter as usize],
+            )?;
+            if let Some(rate) = self.success_rate(letter) {
+                write!(f, " ({:.2}%)", rate * 100.0)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tracks the work done by a search against its `SearchOptions`,
+/// and records its `SearchStats` if they were requested.
+#[derive(Debug)]
+pub(crate) struct Monitor {
+    options: SearchOptions,
+    nodes: u64,
+    stopped: Option<SearchStatus>,
+    stats: Option<SearchStats>,
+    timer: Option<Instant>,
+}
+
+impl Monitor {
+    pub(crate) fn new(options: SearchOptions) -> Monitor {
+        let stats = if options.stats {
+            Some(SearchStats::default())
+        } else {
+            None
+        };
+        Monitor {
+            options,
+            nodes: 0,
+            stopped: None,
+            stats,
+            timer: None,
+        }
+    }
+
+    /// Account for visiting one node.
+    ///
+    /// Returns `false` if the search must stop. Once that happens, every
+    /// subsequent call also returns `false`, so the search unwinds quickly.
+    pub(crate) fn step(&mut self) -> bool {
+        if self.stopped.is_some() {
+            return false;
+        }
+        if self.nodes.is_multiple_of(CHECK_INTERVAL) {
+            if self
+                .options
+                .cancel
+                .as_ref()
+                .is_some_and(CancelToken::is_cancelled)
+            {
+                self.stopped = Some(SearchStatus::Cancelled);
+                return false;
+            }
+            if self
+                .options
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                self.stopped = Some(SearchStatus::BudgetExhausted);
+                return false;
+            }
+        }
+        if self
+            .options
+            .node_budget
+            .is_some_and(|budget| self.nodes >= budget)
+        {
+            self.stopped = Some(SearchStatus::BudgetExhausted);
+            return false;
+        }
+        self.nodes += 1;
+        true
+    }
+
+    /// Why the search was stopped early, if it was.
+    pub(crate) fn stopped(&self) -> Option<SearchStatus> {
+        self.stopped
+    }
+
+    /// Record that `letter` was chosen.
+    pub(crate) fn choose(&mut self, letter: u8) {
+        if let Some(stats) = &mut self.stats {
+            stats.letter_tries[letter as usize] += 1;
+        }
+    }
+
+    /// Record that the letter chosen at `depth` led nowhere.
+    pub(crate) fn backtrack(&mut self, depth: usize) {
+        if let Some(stats) = &mut self.stats {
+            stats.backtracks[depth] += 1;
+        }
+    }
+
+    /// Record that choosing `letter` led to a solution.
+    pub(crate) fn succeed(&mut self, letter: u8) {
+        if let Some(stats) = &mut self.stats {
+            stats.letter_successes[letter as usize] += 1;
+        }
+    }
+
+    /// Start timing a stretch of searching.
+    pub(crate) fn start(&mut self) {
+        if self.stats.is_some() {
+            self.timer = Some(Instant::now());
+        }
+    }
+
+    /// Stop timing a stretch of searching, which found a solution if `found`.
+    pub(crate) fn stop(&mut self, found: bool) {
+        if let (Some(stats), Some(timer)) = (&mut self.stats, self.timer.take()) {
+            stats.total_time += timer.elapsed();
+            if found {
+                stats.solutions += 1;
+                if stats.time_to_first_solution.is_none() {
+                    stats.time_to_first_solution = Some(stats.total_time);
+                }
+            }
+        }
+    }
+
+    /// The statistics gathered so far, if they were requested.
+    pub(crate) fn stats(&self) -> Option<SearchStats> {
+        self.stats.clone().map(|mut stats| {
+            stats.nodes = self.nodes;
+            stats
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unlimited() {
+        let mut monitor = Monitor::new(SearchOptions::default());
+        for _ in 0..10_000 {
+            assert!(monitor.step());
+        }
+        assert_eq!(monitor.stopped(), None);
+        assert_eq!(monitor.stats(), None);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut monitor = Monitor::new(SearchOptions {
+            stats: true,
+            ..SearchOptions::default()
+        });
+        monitor.start();
+        monitor.step();
+        monitor.choose(0);
+        monitor.step();
+        monitor.backtrack(1);
+        monitor.choose(1);
+        monitor.step();
+        monitor.succeed(1);
+        monitor.stop(true);
+
+        let stats = monitor.stats().unwrap();
+        assert_eq!(stats.nodes, 3);
+        assert_eq!(stats.solutions, 1);
+        assert_eq!(stats.backtracks[1], 1);
+        assert_eq!(stats.success_rate(0), Some(0.0));
+        assert_eq!(stats.success_rate(1), Some(1.0));
+        assert_eq!(stats.success_rate(2), None);
+        assert_eq!(stats.time_to_first_solution, Some(stats.total_time));
+    }
+
+    #[test]
+    fn test_node_budget() {
+        let mut monitor = Monitor::new(SearchOptions {
+            node_budget: Some(3),
+            ..SearchOptions::default()
+        });
+        assert!(monitor.step());
+        assert!(monitor.step());
+        assert!(monitor.step());
+        assert!(!monitor.step());
+        assert!(!monitor.step());
+        assert_eq!(monitor.stopped(), Some(SearchStatus::BudgetExhausted));
+    }
+
+    #[test]
+    fn test_deadline() {
+        let mut monitor =
+            Monitor::new(SearchOptions::default().with_timeout(Duration::from_secs(0)));
+        assert!(!monitor.step());
+        assert_eq!(monitor.stopped(), Some(SearchStatus::BudgetExhausted));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let token = CancelToken::new();
+        let mut monitor = Monitor::new(SearchOptions {
+            cancel: Some(token.clone()),
+            ..SearchOptions::default()
+        });
+        assert!(monitor.step());
+        token.cancel();
+        // cancellation is only noticed at the next check
+        while monitor.step() {}
+        assert_eq!(monitor.stopped(), Some(SearchStatus::Cancelled));
+    }
+}
@@ -0,0 +1,317 @@
+use crate::MORSE;
+use std::io::{self, Read, Write};
+
+/// The longest code of any letter.
+const MAX_SYMBOL_LEN: usize = 4;
+
+/// How much of the underlying reader a `MorseDecoder` reads at a time.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Encodes text written to it as spaced morse, and writes that to `W`.
+///
+/// Letters are separated by a space, words by ` / `, and lines are preserved.
+/// As with `smorse`, anything other than `a..=z` and whitespace is dropped.
+/// `MorseEncoder::squashed` instead runs the letters together as `smorse`
+/// does, keeping only the line breaks.
+///
+/// Input is handled a byte at a time: every byte we keep is ascii, and the
+/// bytes of multi-byte UTF-8 characters are never ascii, so a character split
+/// between two writes is dropped exactly as if it had arrived in one piece.
+pub struct MorseEncoder<W: Write> {
+    inner: W,
+    spaced: bool,
+    at_line_start: bool,
+    pending_word_break: bool,
+    out: Vec<u8>,
+}
+
+impl<W: Write> MorseEncoder<W> {
+    pub fn new(inner: W) -> MorseEncoder<W> {
+        MorseEncoder {
+            inner,
+            spaced: true,
+            at_line_start: true,
+            pending_word_break: false,
+            out: Vec::new(),
+        }
+    }
+
+    /// An encoder which, like `smorse`, writes no spaces between letters or words.
+    pub fn squashed(inner: W) -> MorseEncoder<W> {
+        MorseEncoder {
+            spaced: false,
+            ..MorseEncoder::new(inner)
+        }
+    }
+
+    /// Unwrap the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn encode_byte(&mut self, b: u8) {
+        match b {
+            b'a'..=b'z' => {
+                if self.spaced && !self.at_line_start {
+                    if self.pending_word_break {
+                        self.out.extend_from_slice(b" / ");
+                    } else {
+                        self.out.push(b' ');
+                    }
+                }
+                self.out
+                    .extend_from_slice(MORSE[(b - b'a') as usize].as_bytes());
+                self.at_line_start = false;
+                self.pending_word_break = false;
+            }
+            b'\n' => {
+                self.out.push(b'\n');
+                self.at_line_start = true;
+                self.pending_word_break = false;
+            }
+            b' ' | b'\t' if !self.at_line_start => self.pending_word_break = true,
+            _ => {}
+        }
+    }
+}
+
+impl<W: Write> Write for MorseEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &b in buf {
+            self.encode_byte(b);
+        }
+        self.inner.write_all(&self.out)?;
+        self.out.clear();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decodes spaced morse read from `R`, as written by `MorseEncoder`.
+///
+/// Letters may be separated by any run of spaces, `/` separates words, and
+/// lines are preserved. A letter split between two reads of the underlying
+/// reader is decoded as if it had arrived in one piece. A sequence which is
+/// not the code of any letter produces an `InvalidData` error.
+pub struct MorseDecoder<R: Read> {
+    inner: R,
+    chunk: Vec<u8>,
+    token: Vec<u8>,
+    out: Vec<u8>,
+    out_pos: usize,
+    eof: bool,
+}
+
+impl<R: Read> MorseDecoder<R> {
+    pub fn new(inner: R) -> MorseDecoder<R> {
+        MorseDecoder {
+            inner,
+            chunk: vec![0; CHUNK_SIZE],
+            token: Vec::with_capacity(MAX_SYMBOL_LEN),
+            out: Vec::new(),
+            out_pos: 0,
+            eof: false,
+        }
+    }
+
+    /// Unwrap the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn end_token(&mut self) -> io::Result<()> {
+        if self.token.is_empty() {
+            return Ok(());
+        }
+        match MORSE
+            .iter()
+            .position(|sym| sym.as_bytes() == &self.token[..])
+        {
+            Some(idx) => {
+                self.out.push(b'a' + idx as u8);
+                self.token.clear();
+                Ok(())
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{:?} is not the code for any letter",
+                    String::from_utf8_lossy(&self.token)
+                ),
+            )),
+        }
+    }
+
+    fn decode_byte(&mut self, b: u8) -> io::Result<()> {
+        match b {
+            b'.' | b'-' => {
+                if self.token.len() == MAX_SYMBOL_LEN {
+                    self.token.push(b);
+                    return self.end_token();
+                }
+                self.token.push(b);
+            }
+            b'/' => {
+                self.end_token()?;
+                self.out.push(b' ');
+            }
+            b'\n' => {
+                self.end_token()?;
+                self.out.push(b'\n');
+            }
+            b' ' | b'\t' | b'\r' => self.end_token()?,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unexpected byte {:#04x} in morse input", b),
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode at least one more chunk of input, unless the input is exhausted.
+    fn fill(&mut self) -> io::Result<()> {
+        self.out.clear();
+        self.out_pos = 0;
+        let n = self.inner.read(&mut self.chunk)?;
+        if n == 0 {
+            self.eof = true;
+            return self.end_token();
+        }
+        for idx in 0..n {
+            let b = self.chunk[idx];
+            self.decode_byte(b)?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for MorseDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.out_pos == self.out.len() {
+            if self.eof {
+                return Ok(0);
+            }
+            self.fill()?;
+        }
+        let n = buf.len().min(self.out.len() - self.out_pos);
+        buf[..n].copy_from_slice(&self.out[self.out_pos..self.out_pos + n]);
+        self.out_pos += n;
+        Ok(n)
+    }
+}
+
+/// Encode `s` as spaced morse in one go.
+pub fn encode_spaced(s: &str) -> String {
+    let mut encoder = MorseEncoder::new(Vec::new());
+    encoder
+        .write_all(s.as_bytes())
+        .expect("writing to a Vec can't fail");
+    String::from_utf8(encoder.into_inner()).expect("morse is always ascii")
+}
+
+/// Decode spaced morse in one go.
+pub fn decode_spaced(s: &str) -> io::Result<String> {
+    let mut out = String::new();
+    MorseDecoder::new(s.as_bytes()).read_to_string(&mut out)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::smorse;
+
+    /// Reads at most `step` bytes at a time, to exercise buffer boundaries.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        step: usize,
+    }
+
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.step.min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode_spaced("sos"), "... --- ...");
+        assert_eq!(
+            encode_spaced("sos  help\nme"),
+            "... --- ... / .... . .-.. .--.\n-- ."
+        );
+        assert_eq!(encode_spaced(" café! "), "-.-. .- ..-.");
+    }
+
+    #[test]
+    fn test_encode_squashed() {
+        let text = "sos  help\nnaïve me\n";
+        let mut encoder = MorseEncoder::squashed(Vec::new());
+        for piece in text.as_bytes().chunks(3) {
+            encoder.write_all(piece).unwrap();
+        }
+        let expect = text
+            .lines()
+            .map(|line| smorse(line) + "\n")
+            .collect::<String>();
+        assert_eq!(String::from_utf8(encoder.into_inner()).unwrap(), expect);
+    }
+
+    #[test]
+    fn test_encode_split_writes() {
+        let text = "the quick brown fox\njumps over the lazy dog\n";
+        let expect = encode_spaced(text);
+        for step in 1..8 {
+            let mut encoder = MorseEncoder::new(Vec::new());
+            for piece in text.as_bytes().chunks(step) {
+                encoder.write_all(piece).unwrap();
+            }
+            assert_eq!(String::from_utf8(encoder.into_inner()).unwrap(), expect);
+        }
+    }
+
+    #[test]
+    fn test_encode_split_multibyte() {
+        let text = "naïve café";
+        let expect = encode_spaced(text);
+        let mut encoder = MorseEncoder::new(Vec::new());
+        for piece in text.as_bytes().chunks(1) {
+            encoder.write_all(piece).unwrap();
+        }
+        assert_eq!(String::from_utf8(encoder.into_inner()).unwrap(), expect);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = "the quick brown fox\njumps over the lazy dog\n";
+        let encoded = encode_spaced(text);
+        assert_eq!(decode_spaced(&encoded).unwrap(), text);
+        for step in 1..8 {
+            let mut out = String::new();
+            MorseDecoder::new(Trickle {
+                data: encoded.as_bytes(),
+                step,
+            })
+            .read_to_string(&mut out)
+            .unwrap();
+            assert_eq!(out, text);
+        }
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(decode_spaced("...---...").is_err());
+        assert!(decode_spaced("..--").is_err());
+        assert!(decode_spaced("... x").is_err());
+        let err = decode_spaced("... \u{e9}").unwrap_err();
+        assert_eq!(err.to_string(), "unexpected byte 0xc3 in morse input");
+    }
+}
@@ -0,0 +1,347 @@
+use crate::words::Encoded;
+use std::cmp::{Ordering, Reverse};
+use std::ops::Range;
+
+/// Ends each code in the text, so that no match can span two words.
+const SEPARATOR: u8 = b'|';
+
+/// A suffix array over the codes of a word list.
+///
+/// The codes are concatenated, each followed by a separator, and every suffix
+/// of that text is sorted. All suffixes which start with some pattern are then
+/// adjacent, so finding them is a binary search.
+pub struct SuffixIndex<'a> {
+    words: &'a [Encoded],
+    text: Vec<u8>,
+    /// where each word's code starts in `text`
+    starts: Vec<u32>,
+    /// the start of every suffix of `text`, in sorted order
+    sa: Vec<u32>,
+    /// `lcp[i]` is the length of the prefix shared by the suffixes at `sa[i - 1]` and `sa[i]`
+    lcp: Vec<u32>,
+}
+
+/// The longest run of one symbol in any code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run<'a> {
+    pub symbol: char,
+    pub len: usize,
+    /// every word whose code contains a run this long
+    pub words: Vec<&'a Encoded>,
+}
+
+/// How many symbols of each suffix `suffix_array` packs into its sort key.
+const KEY_SYMBOLS: usize = 32;
+
+/// Sort the suffixes of `text`.
+///
+/// Each suffix is compared only up to its first separator, which stands for a
+/// different symbol in each code, so suffixes which tie there are ordered by
+/// position. Codes are short, so most suffixes are ordered entirely by a key
+/// packing their first symbols, two bits apiece.
+fn suffix_array(text: &[u8]) -> Vec<u32> {
+    let symbol = |b: u8| match b {
+        b'-' => 1,
+        b'.' => 2,
+        _ => 3,
+    };
+    // the end of the code covering each position, including its separator
+    let mut ends = vec![0_u32; text.len()];
+    let mut end = text.len();
+    for i in (0..text.len()).rev() {
+        if text[i] == SEPARATOR {
+            end = i + 1;
+        }
+        ends[i] = end as u32;
+    }
+    let suffix = |i: u32| &text[i as usize..ends[i as usize] as usize];
+    let mut keyed = (0..text.len() as u32)
+        .map(|i| {
+            let mut key = 0_u64;
+            for j in 0..KEY_SYMBOLS {
+                key <<= 2;
+                if let Some(&b) = suffix(i).get(j) {
+                    key |= symbol(b);
+                }
+            }
+            (key, i)
+        })
+        .collect::<Vec<_>>();
+    keyed.sort_unstable();
+    // suffixes longer than the key need comparing in full when their keys tie
+    for group in keyed.chunk_by_mut(|a, b| a.0 == b.0) {
+        if group.len() > 1 && suffix(group[0].1).len() > KEY_SYMBOLS {
+            group.sort_unstable_by(|&(_, a), &(_, b)| suffix(a).cmp(suffix(b)).then(a.cmp(&b)));
+        }
+    }
+    keyed.into_iter().map(|(_, i)| i).collect()
+}
+
+/// Kasai's algorithm: the longest common prefix of each suffix and the one sorted before it.
+fn lcp_array(text: &[u8], sa: &[u32]) -> Vec<u32> {
+    let n = text.len();
+    let mut rank = vec![0_u32; n];
+    for (r, &i) in sa.iter().enumerate() {
+        rank[i as usize] = r as u32;
+    }
+    let mut lcp = vec![0_u32; n];
+    let mut h = 0;
+    for i in 0..n {
+        let r = rank[i] as usize;
+        if r == 0 {
+            h = 0;
+            continue;
+        }
+        let j = sa[r - 1] as usize;
+        // separators never match, since each stands for a different symbol
+        while i + h < n && j + h < n && text[i + h] == text[j + h] && text[i + h] != SEPARATOR {
+            h += 1;
+        }
+        lcp[r] = h as u32;
+        h = h.saturating_sub(1);
+    }
+    lcp
+}
+
+impl<'a> SuffixIndex<'a> {
+    pub fn new(words: &'a [Encoded]) -> SuffixIndex<'a> {
+        let mut text = Vec::new();
+        let mut starts = Vec::with_capacity(words.len());
+        for word in words {
+            starts.push(text.len() as u32);
+            text.extend_from_slice(word.code.as_bytes());
+            text.push(SEPARATOR);
+        }
+        let sa = suffix_array(&text);
+        let lcp = lcp_array(&text, &sa);
+        SuffixIndex {
+            words,
+            text,
+            starts,
+            sa,
+            lcp,
+        }
+    }
+
+    /// The index of the word whose code covers `pos` in the text.
+    fn word_at(&self, pos: u32) -> usize {
+        self.starts.partition_point(|&start| start <= pos) - 1
+    }
+
+    /// How many symbols there are from `pos` to the end of its code.
+    fn room(&self, pos: u32) -> usize {
+        let word = self.word_at(pos);
+        self.starts[word] as usize + self.words[word].code.len() - pos as usize
+    }
+
+    fn compare(&self, pos: u32, pattern: &[u8]) -> Ordering {
+        let start = pos as usize;
+        let end = (start + pattern.len()).min(self.text.len());
+        self.text[start..end].cmp(pattern)
+    }
+
+    /// The range of `sa` whose suffixes start with `pattern`.
+    fn range(&self, pattern: &str) -> Range<usize> {
+        let pattern = pattern.as_bytes();
+        if pattern.is_empty() || pattern.iter().any(|&b| b != b'.' && b != b'-') {
+            return 0..0;
+        }
+        let lo = self
+            .sa
+            .partition_point(|&pos| self.compare(pos, pattern) == Ordering::Less);
+        let hi = lo
+            + self.sa[lo..].partition_point(|&pos| self.compare(pos, pattern) == Ordering::Equal);
+        lo..hi
+    }
+
+    /// How many times `pattern` appears across every code.
+    pub fn occurrences(&self, pattern: &str) -> usize {
+        self.range(pattern).len()
+    }
+
+    /// Every word whose code contains `pattern`, in word list order.
+    pub fn words_containing(&self, pattern: &str) -> Vec<&'a Encoded> {
+        let mut found = self.sa[self.range(pattern)]
+            .iter()
+            .map(|&pos| self.word_at(pos))
+            .collect::<Vec<_>>();
+        found.sort_unstable();
+        found.dedup();
+        found.into_iter().map(|idx| &self.words[idx]).collect()
+    }
+
+    /// The longest run of dots and of dashes, with the words which contain them.
+    pub fn longest_runs(&self) -> [Run<'a>; 2] {
+        let run = |symbol: char| {
+            let mut len = 0;
+            let mut lo = 1;
+            // a run of length n contains every shorter run, so search for the longest
+            let mut hi = self.words.iter().map(|e| e.code.len()).max().unwrap_or(0);
+            while lo <= hi {
+                let mid = (lo + hi) / 2;
+                if self.occurrences(&symbol.to_string().repeat(mid)) > 0 {
+                    len = mid;
+                    lo = mid + 1;
+                } else {
+                    hi = mid - 1;
+                }
+            }
+            let words = if len == 0 {
+                Vec::new()
+            } else {
+                self.words_containing(&symbol.to_string().repeat(len))
+            };
+            Run { symbol, len, words }
+        };
+        [run('.'), run('-')]
+    }
+
+    /// The `top` most frequent sequences of length `k`, with how often each appears.
+    ///
+    /// Ties are broken alphabetically.
+    pub fn kgrams(&self, k: usize, top: usize) -> Vec<(String, usize)> {
+        if k == 0 {
+            return Vec::new();
+        }
+        let mut counts = Vec::new();
+        let mut group: Option<(u32, usize)> = None;
+        for (r, &pos) in self.sa.iter().enumerate() {
+            if self.text[pos as usize] == SEPARATOR || self.room(pos) < k {
+                counts.extend(group.take());
+                continue;
+            }
+            match &mut group {
+                Some((_, count)) if self.lcp[r] as usize >= k => *count += 1,
+                _ => {
+                    counts.extend(group.take());
+                    group = Some((pos, 1));
+                }
+            }
+        }
+        counts.extend(group);
+        let mut grams = counts
+            .into_iter()
+            .map(|(pos, count)| {
+                let pos = pos as usize;
+                (
+                    String::from_utf8_lossy(&self.text[pos..pos + k]).into_owned(),
+                    count,
+                )
+            })
+            .collect::<Vec<_>>();
+        grams.sort_by_key(|(gram, count)| (Reverse(*count), gram.clone()));
+        grams.truncate(top);
+        grams
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    const WORDS: &[&str] = &[
+        "sos",
+        "bits",
+        "three",
+        "programmer",
+        "e",
+        "t",
+        "tattoo",
+        "his",
+        "mom",
+        "ooo",
+        "eel",
+        "counterdemonstrations",
+        "counterdemonstration",
+        "zz",
+    ];
+
+    fn words() -> Vec<Encoded> {
+        WORDS.iter().map(|w| Encoded::new(w)).collect()
+    }
+
+    #[test]
+    fn test_suffix_array_sorted() {
+        let words = words();
+        let index = SuffixIndex::new(&words);
+        assert_eq!(index.sa.len(), index.text.len());
+        for w in index.sa.windows(2) {
+            let a = &index.text[w[0] as usize..];
+            let b = &index.text[w[1] as usize..];
+            // compare up to and including the first separator, after which order is by word
+            let cut = |s: &[u8]| {
+                let end = s.iter().position(|&b| b == SEPARATOR).unwrap();
+                s[..=end].to_vec()
+            };
+            assert!(cut(a) <= cut(b));
+        }
+    }
+
+    #[test]
+    fn test_words_containing() {
+        let words = words();
+        let index = SuffixIndex::new(&words);
+        for pattern in &["...", "---", ".-.", "-", "......", "------", "-.-.-.-.-.-."] {
+            let naive = words
+                .iter()
+                .filter(|e| e.code.contains(pattern))
+                .collect::<Vec<_>>();
+            assert_eq!(index.words_containing(pattern), naive, "{}", pattern);
+            let count = words
+                .iter()
+                .map(|e| {
+                    (0..e.code.len())
+                        .filter(|&i| e.code[i..].starts_with(pattern))
+                        .count()
+                })
+                .sum::<usize>();
+            assert_eq!(index.occurrences(pattern), count, "{}", pattern);
+        }
+        assert!(index.words_containing("").is_empty());
+        assert!(index.words_containing(".|.").is_empty());
+    }
+
+    #[test]
+    fn test_longest_runs() {
+        let words = words();
+        let index = SuffixIndex::new(&words);
+        let [dots, dashes] = index.longest_runs();
+        // his: .... .. ...
+        assert_eq!(dots.len, 9);
+        assert_eq!(dots.words[0].word, "his");
+        // tattoo: - .- - - --- ---, and ooo: --- --- ---
+        assert_eq!(dashes.len, 9);
+        let dashes = dashes.words.iter().map(|e| &e.word).collect::<Vec<_>>();
+        assert_eq!(dashes, vec!["tattoo", "ooo"]);
+    }
+
+    #[test]
+    fn test_kgrams() {
+        let words = words();
+        let index = SuffixIndex::new(&words);
+        for k in 1..6 {
+            let mut naive: HashMap<&str, usize> = HashMap::new();
+            for e in &words {
+                for w in 0..(e.code.len() + 1).saturating_sub(k) {
+                    *naive.entry(&e.code[w..w + k]).or_default() += 1;
+                }
+            }
+            let mut naive = naive
+                .into_iter()
+                .map(|(g, c)| (g.to_string(), c))
+                .collect::<Vec<_>>();
+            naive.sort_by_key(|(gram, count)| (Reverse(*count), gram.clone()));
+            assert_eq!(index.kgrams(k, usize::MAX), naive, "k = {}", k);
+        }
+        assert_eq!(index.kgrams(3, 2).len(), 2);
+    }
+
+    #[test]
+    fn test_empty() {
+        let index = SuffixIndex::new(&[]);
+        assert!(index.words_containing(".").is_empty());
+        assert_eq!(index.longest_runs()[0].len, 0);
+        assert!(index.kgrams(2, 10).is_empty());
+    }
+}
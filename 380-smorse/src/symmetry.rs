@@ -0,0 +1,585 @@
+use crate::index::CodeIndex;
+use crate::words::Encoded;
+use serde::Serialize;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+/// A way in which a code can mirror itself or another code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Symmetry {
+    /// the code reads the same backwards
+    Palindrome,
+    /// the code reversed is another word's or phrase's code
+    Reversal,
+    /// the code with dots and dashes swapped is another word's or phrase's code
+    Inversion,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 3] = [
+        Symmetry::Palindrome,
+        Symmetry::Reversal,
+        Symmetry::Inversion,
+    ];
+
+    /// The lowercase name of this symmetry.
+    pub fn name(self) -> &'static str {
+        match self {
+            Symmetry::Palindrome => "palindrome",
+            Symmetry::Reversal => "reversal",
+            Symmetry::Inversion => "inversion",
+        }
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Symmetry, String> {
+        Symmetry::ALL
+            .iter()
+            .cloned()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "unknown symmetry {:?}: expect palindrome, reversal, or inversion",
+                    s
+                )
+            })
+    }
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// What to look for.
+#[derive(Debug, Clone)]
+pub struct SymmetryOptions {
+    pub kinds: Vec<Symmetry>,
+    /// the fewest letters in a word or phrase
+    pub min_letters: usize,
+    /// the most letters in a word or phrase
+    pub max_letters: Option<usize>,
+    /// the most words in a phrase
+    pub max_words: usize,
+    /// keep only this many of the best results
+    pub top: Option<usize>,
+}
+
+impl Default for SymmetryOptions {
+    fn default() -> SymmetryOptions {
+        SymmetryOptions {
+            kinds: Symmetry::ALL.to_vec(),
+            min_letters: 1,
+            max_letters: None,
+            max_words: 1,
+            top: None,
+        }
+    }
+}
+
+impl SymmetryOptions {
+    fn in_bounds(&self, phrase: &[&Encoded]) -> bool {
+        let letters = phrase.iter().map(|e| e.word.chars().count()).sum::<usize>();
+        letters >= self.min_letters && self.max_letters.is_none_or(|max| letters <= max)
+    }
+}
+
+/// A word or phrase with a symmetric code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symmetric<'a> {
+    pub kind: Symmetry,
+    pub phrase: Vec<&'a Encoded>,
+    /// the word or phrase whose code mirrors `phrase`'s; empty for a palindrome
+    pub partner: Vec<&'a Encoded>,
+}
+
+impl<'a> Symmetric<'a> {
+    /// The code of `phrase`.
+    pub fn code(&self) -> String {
+        self.phrase.iter().map(|e| e.code.as_str()).collect()
+    }
+
+    fn code_len(&self) -> usize {
+        self.phrase.iter().map(|e| e.code.len()).sum()
+    }
+
+    fn words(&self) -> usize {
+        self.phrase.len() + self.partner.len()
+    }
+}
+
+/// The words of a phrase, separated by spaces.
+pub fn phrase_text(phrase: &[&Encoded]) -> String {
+    phrase
+        .iter()
+        .map(|e| e.word.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_palindrome(code: &[u8]) -> bool {
+    code.iter().eq(code.iter().rev())
+}
+
+/// Whether `phrase` is one word said more than once, like `sos sos`.
+fn is_repeat(phrase: &[&Encoded]) -> bool {
+    phrase.len() > 1 && phrase.iter().all(|e| e.word == phrase[0].word)
+}
+
+fn reversed(code: &[u8]) -> String {
+    code.iter().rev().map(|&b| b as char).collect()
+}
+
+fn inverted(code: &str) -> String {
+    code.chars()
+        .map(|c| match c {
+            '.' => '-',
+            '-' => '.',
+            c => c,
+        })
+        .collect()
+}
+
+/// The part of a phrase's code which its other side doesn't mirror yet.
+enum Overhang {
+    /// the left words' code runs on past the mirror of the right words' by this much
+    Left(String),
+    /// the right words' code starts this much before the mirror of the left words'
+    Right(String),
+}
+
+/// Builds palindromic phrases from both ends towards the middle.
+///
+/// Each word is added to whichever side is shorter, and must mirror as much of
+/// the other side's overhang as it covers. Once the overhang is a palindrome, so
+/// is the whole phrase. The side to extend depends only on the words so far, so
+/// every phrase is built exactly once.
+///
+/// Most of the work is choosing the last word, so that is a hashed lookup: a
+/// word longer than the overhang it mirrors must leave a palindrome beyond it.
+struct PalindromeSearch<'i, 'a, F> {
+    index: &'i CodeIndex<'a>,
+    max_words: usize,
+    /// each code which starts with a palindrome, under what follows it
+    after_palindrome: HashMap<&'a str, Vec<&'a str>>,
+    /// each code which ends with a palindrome, under what precedes it
+    before_palindrome: HashMap<&'a str, Vec<&'a str>>,
+    left: Vec<&'a Encoded>,
+    /// outermost first
+    right: Vec<&'a Encoded>,
+    found: F,
+}
+
+impl<'i, 'a, F> PalindromeSearch<'i, 'a, F>
+where
+    F: FnMut(Vec<&'a Encoded>),
+{
+    fn visit(&mut self, overhang: Overhang) {
+        let (Overhang::Left(rest) | Overhang::Right(rest)) = &overhang;
+        if is_palindrome(rest.as_bytes()) {
+            let phrase = self.left.iter().chain(self.right.iter().rev()).cloned();
+            (self.found)(phrase.collect());
+        }
+        if self.left.len() + self.right.len() < self.max_words {
+            self.extend(&overhang);
+        }
+    }
+
+    fn extend(&mut self, overhang: &Overhang) {
+        let index = self.index;
+        let last = self.left.len() + self.right.len() + 1 == self.max_words;
+        match overhang {
+            Overhang::Left(over) => {
+                // the next word on the right, reversed, must start like the overhang,
+                // or be all of the overhang and then some
+                let mirror = reversed(over.as_bytes());
+                for &len in index.lengths().iter().take_while(|&&len| len <= over.len()) {
+                    for word in index.lookup(&mirror[mirror.len() - len..]) {
+                        self.right.push(word);
+                        self.visit(Overhang::Left(over[len..].to_string()));
+                        self.right.pop();
+                    }
+                }
+                let longer = if last {
+                    self.after_palindrome
+                        .get(&*mirror)
+                        .cloned()
+                        .unwrap_or_default()
+                } else {
+                    index.codes_with_suffix(&mirror).collect()
+                };
+                for code in longer {
+                    if code.len() > over.len() {
+                        for word in index.lookup(code) {
+                            self.right.push(word);
+                            self.visit(Overhang::Right(
+                                code[..code.len() - over.len()].to_string(),
+                            ));
+                            self.right.pop();
+                        }
+                    }
+                }
+            }
+            Overhang::Right(over) => {
+                // likewise for the next word on the left, without reversing it
+                let mirror = reversed(over.as_bytes());
+                for &len in index.lengths().iter().take_while(|&&len| len <= over.len()) {
+                    for word in index.lookup(&mirror[..len]) {
+                        self.left.push(word);
+                        self.visit(Overhang::Right(over[..over.len() - len].to_string()));
+                        self.left.pop();
+                    }
+                }
+                let longer = if last {
+                    self.before_palindrome
+                        .get(&*mirror)
+                        .cloned()
+                        .unwrap_or_default()
+                } else {
+                    index.codes_with_prefix(&mirror).to_vec()
+                };
+                for code in longer {
+                    if code.len() > over.len() {
+                        for word in index.lookup(code) {
+                            self.left.push(word);
+                            self.visit(Overhang::Left(code[over.len()..].to_string()));
+                            self.left.pop();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Every phrase of one to `max_words` words whose code is a palindrome.
+///
+/// Words may repeat, as in `sos sos`.
+fn palindromic_phrases<'a, F>(index: &CodeIndex<'a>, max_words: usize, found: F)
+where
+    F: FnMut(Vec<&'a Encoded>),
+{
+    let mut after_palindrome: HashMap<_, Vec<_>> = HashMap::new();
+    let mut before_palindrome: HashMap<_, Vec<_>> = HashMap::new();
+    for &code in index.codes_with_prefix("") {
+        for split in 0..=code.len() {
+            let (head, tail) = code.split_at(split);
+            if split > 0 && is_palindrome(head.as_bytes()) {
+                after_palindrome.entry(tail).or_default().push(code);
+            }
+            if split < code.len() && is_palindrome(tail.as_bytes()) {
+                before_palindrome.entry(head).or_default().push(code);
+            }
+        }
+    }
+    let mut search = PalindromeSearch {
+        index,
+        max_words,
+        after_palindrome,
+        before_palindrome,
+        left: Vec::new(),
+        right: Vec::new(),
+        found,
+    };
+    if max_words > 0 {
+        search.extend(&Overhang::Right(String::new()));
+    }
+}
+
+/// The words and phrases whose code is `code`.
+fn partners<'a>(index: &CodeIndex<'a>, code: &str, max_words: usize) -> Vec<Vec<&'a Encoded>> {
+    let mut out = index.lookup(code).map(|e| vec![e]).collect::<Vec<_>>();
+    if max_words >= 2 {
+        out.extend(index.phrases(code, max_words));
+    }
+    out
+}
+
+/// Longer codes rank first, then fewer words, then alphabetically.
+type RankKey = (Reverse<usize>, usize, &'static str, String, String);
+
+struct Ranked<'a> {
+    key: RankKey,
+    symmetric: Symmetric<'a>,
+}
+
+impl<'a> PartialEq for Ranked<'a> {
+    fn eq(&self, other: &Ranked<'a>) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<'a> Eq for Ranked<'a> {}
+
+impl<'a> PartialOrd for Ranked<'a> {
+    fn partial_cmp(&self, other: &Ranked<'a>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> Ord for Ranked<'a> {
+    fn cmp(&self, other: &Ranked<'a>) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+/// Keeps the best results within the bounds, without holding on to the rest.
+struct Ranking<'o, 'a> {
+    options: &'o SymmetryOptions,
+    /// the worst result kept is on top, so that it can be evicted
+    kept: BinaryHeap<Ranked<'a>>,
+}
+
+impl<'o, 'a> Ranking<'o, 'a> {
+    fn push(&mut self, symmetric: Symmetric<'a>) {
+        if !self.options.in_bounds(&symmetric.phrase) {
+            return;
+        }
+        // most results lose on length alone; don't build their names
+        let rank = (Reverse(symmetric.code_len()), symmetric.words());
+        if let (Some(top), Some(worst)) = (self.options.top, self.kept.peek()) {
+            if self.kept.len() == top && rank > (worst.key.0, worst.key.1) {
+                return;
+            }
+        }
+        let key = (
+            Reverse(symmetric.code_len()),
+            symmetric.words(),
+            symmetric.kind.name(),
+            phrase_text(&symmetric.phrase),
+            phrase_text(&symmetric.partner),
+        );
+        if let Some(top) = self.options.top {
+            if self.kept.len() == top {
+                match self.kept.peek() {
+                    Some(worst) if key < worst.key => {
+                        self.kept.pop();
+                    }
+                    _ => return,
+                }
+            }
+        }
+        self.kept.push(Ranked { key, symmetric });
+    }
+
+    fn into_sorted(self) -> Vec<Symmetric<'a>> {
+        self.kept
+            .into_sorted_vec()
+            .into_iter()
+            .map(|ranked| ranked.symmetric)
+            .collect()
+    }
+}
+
+/// Every word or phrase in `index` with a symmetric code, best first.
+///
+/// Longer codes are better, then phrases of fewer words. A pair of single words
+/// which mirror each other is reported once, and a palindromic word repeated
+/// is left out, since it would crowd out every other long palindrome.
+pub fn find_symmetries<'a>(index: &CodeIndex<'a>, options: &SymmetryOptions) -> Vec<Symmetric<'a>> {
+    let mut ranking = Ranking {
+        options,
+        kept: BinaryHeap::new(),
+    };
+    if options.top == Some(0) {
+        return Vec::new();
+    }
+    for &kind in &options.kinds {
+        match kind {
+            Symmetry::Palindrome => {
+                palindromic_phrases(index, options.max_words, |phrase| {
+                    if is_repeat(&phrase) {
+                        return;
+                    }
+                    ranking.push(Symmetric {
+                        kind,
+                        phrase,
+                        partner: Vec::new(),
+                    })
+                });
+            }
+            Symmetry::Reversal | Symmetry::Inversion => {
+                for word in index.words() {
+                    if !options.in_bounds(&[word]) {
+                        continue;
+                    }
+                    let target = if kind == Symmetry::Reversal {
+                        reversed(word.code.as_bytes())
+                    } else {
+                        inverted(&word.code)
+                    };
+                    for partner in partners(index, &target, options.max_words) {
+                        // mirrored single words would otherwise appear twice, and a
+                        // palindrome is its own reversal
+                        if partner.len() == 1 && partner[0].word <= word.word {
+                            continue;
+                        }
+                        ranking.push(Symmetric {
+                            kind,
+                            phrase: vec![word],
+                            partner,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    ranking.into_sorted()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<Encoded> {
+        list.iter().map(|w| Encoded::new(w)).collect()
+    }
+
+    fn found(index: &CodeIndex, options: &SymmetryOptions) -> Vec<(Symmetry, String, String)> {
+        find_symmetries(index, options)
+            .iter()
+            .map(|s| (s.kind, phrase_text(&s.phrase), phrase_text(&s.partner)))
+            .collect()
+    }
+
+    fn only(kind: Symmetry) -> SymmetryOptions {
+        SymmetryOptions {
+            kinds: vec![kind],
+            ..SymmetryOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_palindromes() {
+        // sos is ...---..., e ad is . .--.., and ad pig is .--.. .--...--.
+        let words = words(&["sos", "ab", "n", "ad", "e", "pig"]);
+        let index = CodeIndex::new(&words);
+        let singles = found(&index, &only(Symmetry::Palindrome));
+        assert!(singles.contains(&(Symmetry::Palindrome, "sos".into(), String::new())));
+        assert!(singles.iter().all(|(_, phrase, _)| !phrase.contains(' ')));
+
+        let pairs = found(
+            &index,
+            &SymmetryOptions {
+                max_words: 2,
+                ..only(Symmetry::Palindrome)
+            },
+        );
+        for (_, phrase, _) in &pairs {
+            let code = phrase
+                .split(' ')
+                .map(|w| Encoded::new(w).code)
+                .collect::<String>();
+            assert!(is_palindrome(code.as_bytes()), "{} is not", phrase);
+        }
+        assert!(pairs.iter().any(|(_, phrase, _)| phrase == "e ad"));
+        assert_eq!(pairs[0].1, "ad pig");
+        assert!(pairs.iter().all(|(_, phrase, _)| phrase != "sos sos"));
+        assert!(pairs.iter().any(|(_, phrase, _)| phrase == "sos"));
+    }
+
+    #[test]
+    fn test_palindromic_phrases_complete() {
+        let words = words(&[
+            "sos", "ab", "n", "ad", "e", "t", "it", "ti", "te", "et", "a", "r",
+        ]);
+        let index = CodeIndex::new(&words);
+        for max_words in 0..=4 {
+            let mut fast = Vec::new();
+            palindromic_phrases(&index, max_words, |phrase| fast.push(phrase_text(&phrase)));
+            fast.sort();
+            let mut slow = Vec::new();
+            let mut phrases = vec![Vec::new()];
+            for _ in 0..max_words {
+                phrases = phrases
+                    .iter()
+                    .flat_map(|phrase: &Vec<&Encoded>| {
+                        words.iter().map(move |word| {
+                            let mut longer = phrase.clone();
+                            longer.push(word);
+                            longer
+                        })
+                    })
+                    .collect();
+                for phrase in &phrases {
+                    let code = phrase.iter().map(|e| e.code.as_str()).collect::<String>();
+                    if is_palindrome(code.as_bytes()) {
+                        slow.push(phrase_text(phrase));
+                    }
+                }
+            }
+            slow.sort();
+            assert_eq!(fast, slow, "max_words = {}", max_words);
+        }
+    }
+
+    #[test]
+    fn test_reversal_inversion() {
+        // a: .-  n: -.  e: .  t: -
+        let words = words(&["a", "n", "e", "t"]);
+        let index = CodeIndex::new(&words);
+        assert_eq!(
+            found(&index, &only(Symmetry::Reversal)),
+            vec![(Symmetry::Reversal, "a".into(), "n".into())]
+        );
+        assert_eq!(
+            found(&index, &only(Symmetry::Inversion)),
+            vec![
+                (Symmetry::Inversion, "a".into(), "n".into()),
+                (Symmetry::Inversion, "e".into(), "t".into()),
+            ]
+        );
+        let phrases = found(
+            &index,
+            &SymmetryOptions {
+                max_words: 2,
+                ..only(Symmetry::Reversal)
+            },
+        );
+        assert!(phrases.contains(&(Symmetry::Reversal, "a".into(), "t e".into())));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let words = words(&["sos", "e", "t"]);
+        let index = CodeIndex::new(&words);
+        let options = SymmetryOptions {
+            min_letters: 2,
+            max_letters: Some(3),
+            ..only(Symmetry::Palindrome)
+        };
+        assert_eq!(
+            found(&index, &options),
+            vec![(Symmetry::Palindrome, "sos".into(), String::new())]
+        );
+    }
+
+    #[test]
+    fn test_top() {
+        let words = words(&["sos", "e", "t", "a", "n", "nu"]);
+        let index = CodeIndex::new(&words);
+        let all = find_symmetries(&index, &SymmetryOptions::default());
+        let top = find_symmetries(
+            &index,
+            &SymmetryOptions {
+                top: Some(3),
+                ..SymmetryOptions::default()
+            },
+        );
+        assert_eq!(top[..], all[..3]);
+        assert_eq!(top[0].phrase[0].word, "sos");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("reversal".parse(), Ok(Symmetry::Reversal));
+        assert!("mirror".parse::<Symmetry>().is_err());
+    }
+}
@@ -0,0 +1,406 @@
+use crate::smorse;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::PathBuf;
+
+/// The first bytes of a gzip stream.
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+/// The first bytes of a zstd frame.
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// A word and its squashed code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct Encoded {
+    pub word: String,
+    pub code: String,
+}
+
+impl Encoded {
+    /// Encode `word` with `smorse`.
+    pub fn new(word: &str) -> Encoded {
+        Encoded {
+            word: word.to_string(),
+            code: smorse(word),
+        }
+    }
+}
+
+/// Where a word list comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Source {
+    Stdin,
+    File(PathBuf),
+}
+
+/// `-` means stdin; anything else is a path.
+impl From<&OsStr> for Source {
+    fn from(s: &OsStr) -> Source {
+        if s == "-" {
+            Source::Stdin
+        } else {
+            Source::File(s.into())
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Stdin => f.write_str("<stdin>"),
+            Source::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// A failure to read a word list, with where it happened.
+#[derive(Debug)]
+pub struct WordError {
+    pub source: Source,
+    /// 1-based line number, if the error belongs to a particular line
+    pub line: Option<usize>,
+    pub error: io::Error,
+}
+
+impl WordError {
+    /// Whether this is a line which is not UTF-8, rather than a failure to read at all.
+    pub fn is_invalid_utf8(&self) -> bool {
+        self.line.is_some() && self.error.kind() == io::ErrorKind::InvalidData
+    }
+}
+
+impl fmt::Display for WordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.source, line, self.error),
+            None => write!(f, "{}: {}", self.source, self.error),
+        }
+    }
+}
+
+impl Error for WordError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Wrap `reader` in a decompressor if it starts with a gzip or zstd header.
+fn decompress(mut reader: Box<dyn BufRead>) -> io::Result<Box<dyn BufRead>> {
+    let head = reader.fill_buf()?;
+    if head.starts_with(GZIP_MAGIC) {
+        decompress_gzip(reader)
+    } else if head.starts_with(ZSTD_MAGIC) {
+        decompress_zstd(reader)
+    } else {
+        Ok(reader)
+    }
+}
+
+#[cfg(feature = "compression")]
+fn decompress_gzip(reader: Box<dyn BufRead>) -> io::Result<Box<dyn BufRead>> {
+    Ok(Box::new(BufReader::new(
+        flate2::bufread::MultiGzDecoder::new(reader),
+    )))
+}
+
+#[cfg(feature = "compression")]
+fn decompress_zstd(reader: Box<dyn BufRead>) -> io::Result<Box<dyn BufRead>> {
+    Ok(Box::new(BufReader::new(
+        zstd::stream::read::Decoder::with_buffer(reader)?,
+    )))
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress_gzip(_: Box<dyn BufRead>) -> io::Result<Box<dyn BufRead>> {
+    Err(compression_unsupported("gzip"))
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress_zstd(_: Box<dyn BufRead>) -> io::Result<Box<dyn BufRead>> {
+    Err(compression_unsupported("zstd"))
+}
+
+#[cfg(not(feature = "compression"))]
+fn compression_unsupported(kind: &str) -> io::Error {
+    io::Error::other(format!("{} input needs the `compression` feature", kind))
+}
+
+/// The lines of one source, decompressed if need be.
+///
+/// Trailing `\n` and `\r\n` are removed. A line which is not UTF-8 is an
+/// `InvalidData` error, after which reading continues with the next line.
+pub struct Lines {
+    source: Source,
+    reader: Box<dyn BufRead>,
+    line: usize,
+    buf: Vec<u8>,
+}
+
+impl Lines {
+    /// Open a source, which is decompressed if it is gzip or zstd.
+    pub fn open(source: Source) -> Result<Lines, WordError> {
+        let reader: io::Result<Box<dyn Read>> = match &source {
+            Source::Stdin => Ok(Box::new(io::stdin())),
+            Source::File(path) => File::open(path).map(|file| Box::new(file) as Box<dyn Read>),
+        };
+        match reader {
+            Ok(reader) => Lines::new(source, reader),
+            Err(error) => Err(WordError {
+                source,
+                line: None,
+                error,
+            }),
+        }
+    }
+
+    /// Read lines from `reader`, naming it `source` in errors.
+    pub fn new<R: Read + 'static>(source: Source, reader: R) -> Result<Lines, WordError> {
+        match decompress(Box::new(BufReader::new(reader))) {
+            Ok(reader) => Ok(Lines {
+                source,
+                reader,
+                line: 0,
+                buf: Vec::new(),
+            }),
+            Err(error) => Err(WordError {
+                source,
+                line: None,
+                error,
+            }),
+        }
+    }
+
+    fn error(&self, error: io::Error) -> WordError {
+        WordError {
+            source: self.source.clone(),
+            line: Some(self.line),
+            error,
+        }
+    }
+}
+
+impl Iterator for Lines {
+    type Item = Result<String, WordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buf.clear();
+        self.line += 1;
+        match self.reader.read_until(b'\n', &mut self.buf) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(error) => return Some(Err(self.error(error))),
+        }
+        if self.buf.ends_with(b"\n") {
+            self.buf.pop();
+            if self.buf.ends_with(b"\r") {
+                self.buf.pop();
+            }
+        }
+        match String::from_utf8(std::mem::take(&mut self.buf)) {
+            Ok(line) => Some(Ok(line)),
+            Err(err) => Some(Err(
+                self.error(io::Error::new(io::ErrorKind::InvalidData, err.utf8_error()))
+            )),
+        }
+    }
+}
+
+/// The words of several sources, one after the other.
+///
+/// Each line holds one word; surrounding whitespace is trimmed and blank
+/// lines are skipped. By default, words are deduplicated across every source,
+/// and a line which is not UTF-8 is an error.
+pub struct Words {
+    sources: std::vec::IntoIter<Source>,
+    current: Option<Lines>,
+    strict: bool,
+    seen: Option<HashSet<String>>,
+    skipped: usize,
+}
+
+impl Words {
+    pub fn new<I>(sources: I) -> Words
+    where
+        I: IntoIterator<Item = Source>,
+    {
+        Words {
+            sources: sources.into_iter().collect::<Vec<_>>().into_iter(),
+            current: None,
+            strict: true,
+            seen: Some(HashSet::new()),
+            skipped: 0,
+        }
+    }
+
+    /// Whether a line which is not UTF-8 is an error. If not, it is skipped.
+    pub fn strict(mut self, strict: bool) -> Words {
+        self.strict = strict;
+        self
+    }
+
+    /// Whether to yield each word only the first time it appears.
+    pub fn dedup(mut self, dedup: bool) -> Words {
+        self.seen = if dedup { Some(HashSet::new()) } else { None };
+        self
+    }
+
+    /// How many lines have been skipped because they were not UTF-8.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+}
+
+impl Iterator for Words {
+    type Item = Result<String, WordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let lines = match &mut self.current {
+                Some(lines) => lines,
+                None => match Lines::open(self.sources.next()?) {
+                    Ok(lines) => self.current.get_or_insert(lines),
+                    Err(err) => return Some(Err(err)),
+                },
+            };
+            let line = match lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(err)) if !self.strict && err.is_invalid_utf8() => {
+                    self.skipped += 1;
+                    continue;
+                }
+                Some(Err(err)) => return Some(Err(err)),
+                None => {
+                    self.current = None;
+                    continue;
+                }
+            };
+            let word = line.trim();
+            if word.is_empty() {
+                continue;
+            }
+            if let Some(seen) = &mut self.seen {
+                if !seen.insert(word.to_string()) {
+                    continue;
+                }
+            }
+            return Some(Ok(word.to_string()));
+        }
+    }
+}
+
+/// Read every word from `sources`, strictly and without duplicates.
+pub fn read_words<I>(sources: I) -> Result<Vec<String>, WordError>
+where
+    I: IntoIterator<Item = Source>,
+{
+    Words::new(sources).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    fn lines(data: &[u8]) -> Vec<Result<String, String>> {
+        Lines::new(Source::Stdin, Cursor::new(data.to_vec()))
+            .unwrap()
+            .map(|line| line.map_err(|err| err.to_string()))
+            .collect()
+    }
+
+    /// Write `data` to a fresh file in the temp directory.
+    fn temp_file(name: &str, data: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("smorse-{}-{}", std::process::id(), name));
+        File::create(&path).unwrap().write_all(data).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_lines() {
+        assert_eq!(
+            lines(b"sos\r\nhelp\n\nme"),
+            vec![
+                Ok("sos".to_string()),
+                Ok("help".to_string()),
+                Ok(String::new()),
+                Ok("me".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let got = lines(b"sos\nh\xffelp\nme\n");
+        assert_eq!(got.len(), 3);
+        assert!(got[1].as_ref().unwrap_err().starts_with("<stdin>:2: "));
+        assert_eq!(got[2], Ok("me".to_string()));
+    }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn test_compressed() {
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(b"sos\nhelp\n").unwrap();
+        let gz = gz.finish().unwrap();
+        assert_eq!(
+            lines(&gz),
+            vec![Ok("sos".to_string()), Ok("help".to_string())]
+        );
+
+        let zst = zstd::encode_all(&b"sos\nhelp\n"[..], 0).unwrap();
+        assert_eq!(
+            lines(&zst),
+            vec![Ok("sos".to_string()), Ok("help".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_merge_dedup() {
+        let a = temp_file("merge-a", b"sos\nhelp\n");
+        let b = temp_file("merge-b", b" help \nme\nsos\n");
+        let sources = vec![Source::File(a.clone()), Source::File(b.clone())];
+        assert_eq!(
+            read_words(sources.clone()).unwrap(),
+            vec!["sos", "help", "me"]
+        );
+        assert_eq!(
+            Words::new(sources)
+                .dedup(false)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec!["sos", "help", "help", "me", "sos"]
+        );
+        std::fs::remove_file(a).unwrap();
+        std::fs::remove_file(b).unwrap();
+    }
+
+    #[test]
+    fn test_strict() {
+        let path = temp_file("strict", b"sos\nh\xffelp\nme\n");
+        let err = read_words(vec![Source::File(path.clone())]).unwrap_err();
+        assert_eq!(err.line, Some(2));
+        assert!(err.is_invalid_utf8());
+        assert!(err
+            .to_string()
+            .starts_with(&format!("{}:2: ", path.display())));
+
+        let mut words = Words::new(vec![Source::File(path.clone())]).strict(false);
+        assert_eq!(
+            words.by_ref().collect::<Result<Vec<_>, _>>().unwrap(),
+            vec!["sos", "me"]
+        );
+        assert_eq!(words.skipped(), 1);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let err = read_words(vec![Source::File("/no/such/word/list".into())]).unwrap_err();
+        assert_eq!(err.line, None);
+        assert_eq!(err.error.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,58 @@
+/* Exercises the C interface; exits nonzero on the first failure. */
+#include <stdio.h>
+#include <string.h>
+
+#include "smorse.h"
+
+#define CODE "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--"
+
+static int failures = 0;
+
+static void check_str(const char *what, char *got, const char *expect) {
+    if (got == NULL && expect == NULL) {
+        return;
+    }
+    if (got == NULL || expect == NULL || strcmp(got, expect) != 0) {
+        fprintf(stderr, "%s: expected %s, got %s\n", what, expect ? expect : "NULL",
+                got ? got : "NULL");
+        failures++;
+    }
+    smorse_string_free(got);
+}
+
+int main(void) {
+    check_str("encode", smorse_encode("sos"), "...---...");
+    check_str("encode_spaced", smorse_encode_spaced("sos me"), "... --- ... / -- .");
+    check_str("decode", smorse_decode("... --- ... / -- ."), "sos me");
+    check_str("decode invalid", smorse_decode("..--..--"), NULL);
+    check_str("smalpha", smorse_smalpha(CODE), "esfaboluzpvcwdijrmxqhgktny");
+    check_str("smalpha none", smorse_smalpha("-.-"), NULL);
+
+    SmorseSmalphaIter *iter = smorse_smalpha_all(CODE);
+    int count = 0;
+    char *perm;
+    while ((perm = smorse_smalpha_next(iter)) != NULL) {
+        if (strlen(perm) != 26) {
+            fprintf(stderr, "smalpha_all: bad permutation %s\n", perm);
+            failures++;
+        }
+        smorse_string_free(perm);
+        count++;
+    }
+    smorse_smalpha_free(iter);
+    if (count != 41) {
+        fprintf(stderr, "smalpha_all: expected 41 solutions, got %d\n", count);
+        failures++;
+    }
+
+    uint8_t *buf = smorse_alloc(16);
+    memset(buf, '.', 16);
+    smorse_dealloc(buf, 16);
+    smorse_dealloc(NULL, 16);
+    smorse_string_free(NULL);
+
+    if (failures == 0) {
+        printf("ok\n");
+    }
+    return failures == 0 ? 0 : 1;
+}
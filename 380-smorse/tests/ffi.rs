@@ -0,0 +1,57 @@
+//! Builds the `cdylib`, compiles `tests/c/harness.c` against it, and runs it.
+#![cfg(all(feature = "ffi", target_os = "linux"))]
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn test_c_harness() {
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // the cdylib isn't a declared crate type, so that the no_std core can build without
+    // a panic handler; build it here, in a target directory of its own
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("ffi");
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+    let status = Command::new(&cargo)
+        .current_dir(&manifest)
+        .args([
+            "rustc",
+            "--lib",
+            "--crate-type",
+            "cdylib",
+            "--no-default-features",
+            "--features",
+            "ffi",
+            "--target-dir",
+        ])
+        .arg(&target_dir)
+        .status()
+        .unwrap_or_else(|err| panic!("failed to run {}: {}", cargo, err));
+    assert!(status.success(), "building the cdylib failed");
+    let lib_dir = target_dir.join("debug");
+    let harness = lib_dir.join("smorse-c-harness");
+
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".into());
+    let status = Command::new(&cc)
+        .arg(manifest.join("tests/c/harness.c"))
+        .arg("-I")
+        .arg(manifest.join("include"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg("-lsmorse")
+        .arg("-o")
+        .arg(&harness)
+        .status()
+        .unwrap_or_else(|err| panic!("failed to run {}: {}", cc, err));
+    assert!(status.success(), "compiling the C harness failed");
+
+    let output = Command::new(&harness)
+        .env("LD_LIBRARY_PATH", &lib_dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "C harness failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,92 @@
+// A JS-friendly wrapper around the WebAssembly build of smorse.
+//
+// Build the module with wasm/test.sh, or:
+//   cargo rustc --lib --crate-type cdylib --target wasm32-unknown-unknown --release \
+//       --no-default-features --features ffi
+
+const encoder = new TextEncoder();
+const decoder = new TextDecoder();
+
+/**
+ * Instantiate smorse from the bytes of `smorse.wasm`.
+ *
+ * Works anywhere `WebAssembly.instantiate` does, including browsers
+ * (pass the result of `fetch(...).arrayBuffer()`) and node.
+ */
+export async function load(bytes) {
+  const { instance } = await WebAssembly.instantiate(bytes, {});
+  return new Smorse(instance.exports);
+}
+
+class Smorse {
+  constructor(exports) {
+    this.exports = exports;
+  }
+
+  // copy a JS string into wasm memory as a nul-terminated string
+  withCString(s, f) {
+    const bytes = encoder.encode(s);
+    const len = bytes.length + 1;
+    const ptr = this.exports.smorse_alloc(len);
+    const mem = new Uint8Array(this.exports.memory.buffer, ptr, len);
+    mem.set(bytes);
+    mem[bytes.length] = 0;
+    try {
+      return f(ptr);
+    } finally {
+      this.exports.smorse_dealloc(ptr, len);
+    }
+  }
+
+  // take ownership of a string returned by the library; null stays null
+  takeString(ptr) {
+    if (ptr === 0) {
+      return null;
+    }
+    const mem = new Uint8Array(this.exports.memory.buffer);
+    let end = ptr;
+    while (mem[end] !== 0) {
+      end++;
+    }
+    const s = decoder.decode(mem.subarray(ptr, end));
+    this.exports.smorse_string_free(ptr);
+    return s;
+  }
+
+  call(name, s) {
+    return this.withCString(s, (ptr) => this.takeString(this.exports[name](ptr)));
+  }
+
+  /** Squashed morse for `text`. */
+  encode(text) {
+    return this.call("smorse_encode", text);
+  }
+
+  /** Spaced morse for `text`. */
+  encodeSpaced(text) {
+    return this.call("smorse_encode_spaced", text);
+  }
+
+  /** Text for spaced morse, or null if it is invalid. */
+  decode(code) {
+    return this.call("smorse_decode", code);
+  }
+
+  /** The first alphabet permutation producing `code`, or null. */
+  smalpha(code) {
+    return this.call("smorse_smalpha", code);
+  }
+
+  /** Every alphabet permutation producing `code`, lazily. */
+  *smalphaAll(code) {
+    const iter = this.withCString(code, (ptr) => this.exports.smorse_smalpha_all(ptr));
+    try {
+      let perm;
+      while ((perm = this.takeString(this.exports.smorse_smalpha_next(iter))) !== null) {
+        yield perm;
+      }
+    } finally {
+      this.exports.smorse_smalpha_free(iter);
+    }
+  }
+}
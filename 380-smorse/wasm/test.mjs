@@ -0,0 +1,25 @@
+// Run with: node wasm/test.mjs path/to/smorse.wasm
+import assert from "node:assert/strict";
+import { readFile } from "node:fs/promises";
+import { load } from "./smorse.mjs";
+
+const CODE =
+  "......-..--...---.-....---...--....--.-..---.....---.-.---..---.-....--.-.---.-.--";
+
+const smorse = await load(await readFile(process.argv[2]));
+
+assert.equal(smorse.encode("sos"), "...---...");
+assert.equal(smorse.encodeSpaced("sos me"), "... --- ... / -- .");
+assert.equal(smorse.decode("... --- ... / -- ."), "sos me");
+assert.equal(smorse.decode("..--..--"), null);
+assert.equal(smorse.smalpha(CODE), "esfaboluzpvcwdijrmxqhgktny");
+assert.equal(smorse.smalpha("-.-"), null);
+
+const all = [...smorse.smalphaAll(CODE)];
+assert.equal(all.length, 41);
+assert.equal(all[0], "esfaboluzpvcwdijrmxqhgktny");
+for (const perm of all) {
+  assert.equal(smorse.encode(perm), CODE);
+}
+
+console.log("ok");
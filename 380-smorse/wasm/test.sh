@@ -0,0 +1,7 @@
+#!/bin/sh
+# Build the WebAssembly module and run the JS wrapper's tests under node.
+set -e
+cd "$(dirname "$0")/.."
+cargo rustc --lib --crate-type cdylib --target wasm32-unknown-unknown --release \
+    --no-default-features --features ffi
+node wasm/test.mjs target/wasm32-unknown-unknown/release/smorse.wasm
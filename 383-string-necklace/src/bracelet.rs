@@ -0,0 +1,135 @@
+use crate::Necklace;
+use std::fmt;
+
+/// A necklace which can also be turned over, so that reflections are equivalent too.
+///
+/// It's stored as the least rotation of either the sequence or its reverse.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Bracelet<T>(Necklace<T>);
+
+/// A bracelet of characters.
+pub type StringBracelet = Bracelet<char>;
+
+impl Bracelet<char> {
+    pub fn new<T>(s: T) -> StringBracelet
+    where
+        T: AsRef<str>,
+    {
+        s.as_ref().chars().collect()
+    }
+}
+
+impl<T: Ord + Clone> Bracelet<T> {
+    pub fn from_slice(items: &[T]) -> Bracelet<T> {
+        items.to_vec().into()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The least rotation of the sequence or its reverse.
+    pub fn as_slice(&self) -> &[T] {
+        self.0.as_slice()
+    }
+
+    /// The canonical orientation, as a necklace.
+    pub fn as_necklace(&self) -> &Necklace<T> {
+        &self.0
+    }
+
+    /// Whether turning this over gives the same necklace.
+    ///
+    /// `abcba` is achiral: its reverse is itself. `abcd` is chiral: its
+    /// reverse `dcba` is the necklace `adcb`, a different necklace but the
+    /// same bracelet.
+    pub fn is_achiral(&self) -> bool {
+        self.0 == self.as_slice().iter().rev().cloned().collect()
+    }
+}
+
+impl<T: Ord + Clone> From<Vec<T>> for Bracelet<T> {
+    fn from(items: Vec<T>) -> Bracelet<T> {
+        let reversed = items.iter().rev().cloned().collect::<Necklace<_>>();
+        let forward = Necklace::from(items);
+        Bracelet(forward.min(reversed))
+    }
+}
+
+impl<T: Ord + Clone> std::iter::FromIterator<T> for Bracelet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Bracelet<T> {
+        iter.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+impl fmt::Display for StringBracelet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StringNecklace;
+    use rstest::*;
+
+    #[rstest(
+        a,
+        b,
+        expect_equal,
+        case("abc", "cba", true),
+        case("abc", "bca", true),
+        case("nicole", "elocin", true),
+        case("nicole", "coneli", false),
+        case("abcd", "adcb", true),
+        case("abcd", "abdc", false),
+        case("x", "xx", false),
+        case("", "", true)
+    )]
+    fn test_equivalence(a: &str, b: &str, expect_equal: bool) {
+        assert_eq!(
+            StringBracelet::new(a) == StringBracelet::new(b),
+            expect_equal
+        );
+    }
+
+    #[rstest(
+        s,
+        expect,
+        case("", true),
+        case("a", true),
+        case("abcba", true),
+        case("abab", true),
+        case("abc", false),
+        case("aabcb", true),
+        case("abcd", false),
+        case("aabab", true),
+        case("aabbab", false),
+        case("nicole", false)
+    )]
+    fn test_achiral(s: &str, expect: bool) {
+        let bracelet = StringBracelet::new(s);
+        assert_eq!(bracelet.is_achiral(), expect);
+        // a chiral bracelet is two necklaces, one for each side
+        let reversed: String = s.chars().rev().collect();
+        assert_eq!(
+            StringNecklace::new(s) == StringNecklace::new(reversed),
+            expect
+        );
+    }
+
+    #[test]
+    fn test_canonical() {
+        assert_eq!(StringBracelet::new("cba").to_string(), "abc");
+        assert_eq!(StringBracelet::new("bcbaa").to_string(), "aabcb");
+        assert_eq!(
+            Bracelet::from_slice(&[1, 3, 2]).as_necklace(),
+            &Necklace::from(vec![1, 2, 3])
+        );
+    }
+}
@@ -0,0 +1,469 @@
+//! The Burrows–Wheeler transform and its bijective variant, with the
+//! move-to-front and run-length stages which make it compress.
+//!
+//! Like `Necklace`, the BWT works by sorting rotations: it keeps the last
+//! symbol of each rotation, in sorted order, which tends to group equal
+//! symbols together.
+
+use crate::lyndon::lyndon_factors;
+use std::ops::Range;
+
+/// The start of every rotation of `s`, in sorted order.
+fn sort_rotations(s: &[u8]) -> Vec<usize> {
+    let whole = 0..s.len();
+    sort_cycles(s, std::slice::from_ref(&whole))
+}
+
+/// Every position of `s`, sorted by the rotation starting there of the cycle
+/// which covers it, repeated forever. The cycles must cover `s`.
+///
+/// Rotations are sorted by prefix doubling: after each round, they're ranked
+/// by their first `k` symbols, and a rotation's first `2k` are its first `k`
+/// followed by the first `k` of the rotation `k` further on. Repetitions of
+/// `u` and `v` which agree for `u.len() + v.len()` symbols agree forever, so
+/// that many is enough. Rotations which are equal forever keep equal ranks,
+/// in no particular order.
+fn sort_cycles(s: &[u8], cycles: &[Range<usize>]) -> Vec<usize> {
+    let n = s.len();
+    // each position's cycle, as (start, length)
+    let mut cycle = vec![(0, 0); n];
+    for c in cycles {
+        for slot in &mut cycle[c.clone()] {
+            *slot = (c.start, c.len());
+        }
+    }
+    let step = |i: usize, k: usize| {
+        let (start, len) = cycle[i];
+        start + (i - start + k) % len
+    };
+    let mut lens: Vec<usize> = cycles.iter().map(|c| c.len()).collect();
+    lens.sort_unstable_by(|a, b| b.cmp(a));
+    let enough = lens.iter().take(2).sum::<usize>();
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&i| s[i]);
+    let mut class = vec![0; n];
+    for w in 1..n {
+        let same = s[order[w]] == s[order[w - 1]];
+        class[order[w]] = class[order[w - 1]] + if same { 0 } else { 1 };
+    }
+    let mut shifted = vec![0; n];
+    let mut next = vec![0; n];
+    let mut k = 1;
+    while k < enough && class[order[n - 1]] + 1 < n {
+        // `order` is sorted by the first k symbols, so this is sorted by the second k
+        for (slot, &i) in shifted.iter_mut().zip(&order) {
+            let (_, len) = cycle[i];
+            *slot = step(i, len - k % len);
+        }
+        let mut counts = vec![0; n + 1];
+        for &i in &shifted {
+            counts[class[i] + 1] += 1;
+        }
+        for c in 1..=n {
+            counts[c] += counts[c - 1];
+        }
+        for &i in &shifted {
+            order[counts[class[i]]] = i;
+            counts[class[i]] += 1;
+        }
+        let key = |i: usize| (class[i], class[step(i, k)]);
+        next[order[0]] = 0;
+        for w in 1..n {
+            let same = key(order[w]) == key(order[w - 1]);
+            next[order[w]] = next[order[w - 1]] + if same { 0 } else { 1 };
+        }
+        std::mem::swap(&mut class, &mut next);
+        k *= 2;
+    }
+    order
+}
+
+/// The Burrows–Wheeler transform of `s`: the last symbol of each of its
+/// rotations, in sorted order, and the row at which `s` itself sorts.
+pub fn bwt(s: &[u8]) -> (Vec<u8>, usize) {
+    let n = s.len();
+    let order = sort_rotations(s);
+    let last = order.iter().map(|&i| s[(i + n - 1) % n]).collect();
+    let primary = order.iter().position(|&i| i == 0).unwrap_or(0);
+    (last, primary)
+}
+
+/// For each row of the sorted rotations, the row of the rotation one symbol to its left.
+///
+/// The `i`th occurrence of a symbol in the last column is the `i`th in the
+/// first, so this maps each last-column symbol to its place in the first.
+fn last_to_first(last: &[u8]) -> Vec<usize> {
+    let mut starts = [0; 257];
+    for &b in last {
+        starts[b as usize + 1] += 1;
+    }
+    for b in 1..starts.len() {
+        starts[b] += starts[b - 1];
+    }
+    last.iter()
+        .map(|&b| {
+            let row = starts[b as usize];
+            starts[b as usize] += 1;
+            row
+        })
+        .collect()
+}
+
+/// Undo `bwt`.
+pub fn inverse_bwt(last: &[u8], primary: usize) -> Vec<u8> {
+    let lf = last_to_first(last);
+    let mut out = vec![0; last.len()];
+    let mut row = primary;
+    for slot in out.iter_mut().rev() {
+        *slot = last[row];
+        row = lf[row];
+    }
+    out
+}
+
+/// The bijective Burrows–Wheeler transform of Gil and Scott.
+///
+/// Instead of rotating `s` as a whole, this rotates each of its Lyndon
+/// factors, and sorts every rotation of every factor as if it repeated
+/// forever. That needs no primary index, and every string is the transform of
+/// exactly one other.
+pub fn bijective_bwt(s: &[u8]) -> Vec<u8> {
+    let mut cycles = Vec::new();
+    let mut start = 0;
+    for factor in lyndon_factors(s) {
+        cycles.push(start..start + factor.len());
+        start += factor.len();
+    }
+    let order = sort_cycles(s, &cycles);
+    let mut prev = vec![0; s.len()];
+    for c in &cycles {
+        for i in c.clone() {
+            prev[i] = if i == c.start { c.end - 1 } else { i - 1 };
+        }
+    }
+    order.into_iter().map(|i| s[prev[i]]).collect()
+}
+
+/// Undo `bijective_bwt`.
+///
+/// Following the last-to-first mapping from each row not yet visited walks one
+/// cycle, which spells out one Lyndon factor. Visiting rows in order finds the
+/// factors from least to greatest, so they're joined in reverse.
+pub fn inverse_bijective_bwt(last: &[u8]) -> Vec<u8> {
+    let lf = last_to_first(last);
+    // first_to_last inverts it, so walking it reads each factor forwards
+    let mut first_to_last = vec![0; last.len()];
+    for (row, &f) in lf.iter().enumerate() {
+        first_to_last[f] = row;
+    }
+    let mut sorted = last.to_vec();
+    sorted.sort_unstable();
+    let mut seen = vec![false; last.len()];
+    let mut factors = Vec::new();
+    for start in 0..last.len() {
+        if seen[start] {
+            continue;
+        }
+        let mut factor = Vec::new();
+        let mut row = start;
+        while !seen[row] {
+            seen[row] = true;
+            factor.push(sorted[row]);
+            row = first_to_last[row];
+        }
+        factors.push(factor);
+    }
+    factors.into_iter().rev().flatten().collect()
+}
+
+/// Replace each byte with how many distinct bytes were used since it last was.
+///
+/// After a BWT, that's mostly small numbers, and mostly zeros.
+pub fn move_to_front(s: &[u8]) -> Vec<u8> {
+    let mut recent: Vec<u8> = (0..=255).collect();
+    s.iter()
+        .map(|&b| {
+            let idx = recent.iter().position(|&r| r == b).unwrap_or_default();
+            recent.remove(idx);
+            recent.insert(0, b);
+            idx as u8
+        })
+        .collect()
+}
+
+/// Undo `move_to_front`.
+pub fn inverse_move_to_front(s: &[u8]) -> Vec<u8> {
+    let mut recent: Vec<u8> = (0..=255).collect();
+    s.iter()
+        .map(|&idx| {
+            let b = recent.remove(idx as usize);
+            recent.insert(0, b);
+            b
+        })
+        .collect()
+}
+
+/// Run-length encode: after any two equal bytes, a count of how many more follow.
+///
+/// Runs longer than that can count continue as a new run, and lone bytes
+/// cost nothing extra.
+pub fn run_length_encode(s: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(s.len());
+    let mut i = 0;
+    while i < s.len() {
+        let b = s[i];
+        let run = s[i..].iter().take(257).take_while(|&&c| c == b).count();
+        out.push(b);
+        if run >= 2 {
+            out.push(b);
+            out.push((run - 2) as u8);
+        }
+        i += run;
+    }
+    out
+}
+
+/// Undo `run_length_encode`, or `None` if `s` ends partway through a run.
+pub fn run_length_decode(s: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(s.len());
+    let mut i = 0;
+    while i < s.len() {
+        let b = s[i];
+        if s.get(i + 1) == Some(&b) {
+            let more = *s.get(i + 2)? as usize;
+            out.extend(std::iter::repeat_n(b, 2 + more));
+            i += 3;
+        } else {
+            out.push(b);
+            i += 1;
+        }
+    }
+    Some(out)
+}
+
+/// Marks data which `compress` transformed with the plain BWT.
+const PLAIN: u8 = 0;
+/// Marks data which `compress` transformed with the bijective BWT.
+const BIJECTIVE: u8 = 1;
+
+/// Compress `s` by transforming it, then moving symbols to the front, then
+/// encoding runs.
+///
+/// The first byte records which transform was used, and for the plain BWT,
+/// the next eight are its primary index, little-endian.
+pub fn compress(s: &[u8], bijective: bool) -> Vec<u8> {
+    let mut out = Vec::new();
+    let last = if bijective {
+        out.push(BIJECTIVE);
+        bijective_bwt(s)
+    } else {
+        let (last, primary) = bwt(s);
+        out.push(PLAIN);
+        out.extend_from_slice(&(primary as u64).to_le_bytes());
+        last
+    };
+    out.extend(run_length_encode(&move_to_front(&last)));
+    out
+}
+
+/// Undo `compress`, or `None` if `data` isn't something it produced.
+pub fn decompress(data: &[u8]) -> Option<Vec<u8>> {
+    let (&mode, rest) = data.split_first()?;
+    match mode {
+        BIJECTIVE => {
+            let last = inverse_move_to_front(&run_length_decode(rest)?);
+            Some(inverse_bijective_bwt(&last))
+        }
+        PLAIN if rest.len() >= 8 => {
+            let (primary, rest) = rest.split_at(8);
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(primary);
+            let primary = u64::from_le_bytes(bytes) as usize;
+            let last = inverse_move_to_front(&run_length_decode(rest)?);
+            if primary >= last.len().max(1) {
+                return None;
+            }
+            Some(inverse_bwt(&last, primary))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    /// A pseudorandom string of `len` bytes below `limit`.
+    fn pseudorandom(len: usize, limit: u8, seed: u64) -> Vec<u8> {
+        let mut state = seed | 1;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state % limit as u64) as u8
+            })
+            .collect()
+    }
+
+    fn samples() -> Vec<Vec<u8>> {
+        let mut samples: Vec<Vec<u8>> = vec![
+            b"".to_vec(),
+            b"a".to_vec(),
+            b"banana".to_vec(),
+            b"abracadabra".to_vec(),
+            b"aaaaaaaa".to_vec(),
+            b"abababab".to_vec(),
+            b"mississippi".to_vec(),
+            b"SIX.MIXED.PIXIES.SIFT.SIXTY.PIXIE.DUST.BOXES".to_vec(),
+            (0..=255).collect(),
+            (0..=255).rev().collect(),
+        ];
+        for seed in 1..20 {
+            samples.push(pseudorandom(seed as usize * 37, 2 + seed as u8 % 5, seed));
+        }
+        samples
+    }
+
+    #[test]
+    fn test_sort_rotations() {
+        for s in samples() {
+            let rotation = |i: usize| [&s[i..], &s[..i]].concat();
+            let rotations = sort_rotations(&s)
+                .into_iter()
+                .map(rotation)
+                .collect::<Vec<_>>();
+            assert!(rotations.windows(2).all(|w| w[0] <= w[1]));
+            assert_eq!(rotations.len(), s.len());
+        }
+    }
+
+    #[rstest(
+        s,
+        last,
+        primary,
+        case(b"banana", b"nnbaaa", 3),
+        case(b"abracadabra", b"rdarcaaaabb", 2)
+    )]
+    fn test_bwt(s: &[u8], last: &[u8], primary: usize) {
+        assert_eq!(bwt(s), (last.to_vec(), primary));
+    }
+
+    #[test]
+    fn test_bwt_round_trip() {
+        for s in samples() {
+            let (last, primary) = bwt(&s);
+            assert_eq!(inverse_bwt(&last, primary), s);
+        }
+    }
+
+    /// The bijective BWT by its definition, comparing each rotation repeated to a common length.
+    fn naive_bijective_bwt(s: &[u8]) -> Vec<u8> {
+        let mut rotations = Vec::new();
+        for factor in lyndon_factors(s) {
+            for i in 0..factor.len() {
+                rotations.push([&factor[i..], &factor[..i]].concat());
+            }
+        }
+        let len = 2 * s.len().max(1);
+        rotations.sort_by_key(|r| r.iter().cycle().take(len).copied().collect::<Vec<_>>());
+        rotations.iter().map(|r| r[r.len() - 1]).collect()
+    }
+
+    #[test]
+    fn test_bijective_bwt() {
+        // the example from Gil and Scott's paper
+        assert_eq!(
+            bijective_bwt(b"SIX.MIXED.PIXIES.SIFT.SIXTY.PIXIE.DUST.BOXES"),
+            b"STEYDST.E.IXXIIXXSMPPXS.B..EE..SUSFXDIOIIIIT".to_vec()
+        );
+        for s in samples() {
+            let last = bijective_bwt(&s);
+            assert_eq!(last, naive_bijective_bwt(&s));
+            assert_eq!(inverse_bijective_bwt(&last), s);
+        }
+    }
+
+    #[test]
+    fn test_bijective_bwt_long_factors() {
+        // two long factors which agree for most of their length
+        let mut s = b"ab".repeat(100_000);
+        s.extend_from_slice(b"aaa");
+        s.extend(b"ab".repeat(100_000));
+        let last = bijective_bwt(&s);
+        assert_eq!(inverse_bijective_bwt(&last), s);
+
+        let mut s = b"ab".repeat(300);
+        s.extend_from_slice(b"aaa");
+        s.extend(b"ab".repeat(300));
+        assert_eq!(bijective_bwt(&s), naive_bijective_bwt(&s));
+    }
+
+    #[test]
+    fn test_bijective_is_a_bijection() {
+        // every string of these lengths over a small alphabet is exactly one string's transform
+        for len in 0..=6 {
+            let mut seen = std::collections::HashSet::new();
+            let words = (0..len).fold(vec![Vec::new()], |words, _| {
+                words
+                    .iter()
+                    .flat_map(|w: &Vec<u8>| (b'a'..=b'c').map(move |c| [&w[..], &[c]].concat()))
+                    .collect()
+            });
+            for w in &words {
+                let last = bijective_bwt(w);
+                assert_eq!(&inverse_bijective_bwt(&last), w);
+                assert!(seen.insert(last));
+            }
+        }
+    }
+
+    #[test]
+    fn test_move_to_front() {
+        assert_eq!(move_to_front(b"aaabbb"), vec![97, 0, 0, 98, 0, 0]);
+        for s in samples() {
+            assert_eq!(inverse_move_to_front(&move_to_front(&s)), s);
+        }
+    }
+
+    #[rstest(
+        s,
+        expect,
+        case(b"", b""),
+        case(b"abc", b"abc"),
+        case(b"aab", b"aa\x00b"),
+        case(b"aaaaab", b"aa\x03b")
+    )]
+    fn test_run_length(s: &[u8], expect: &[u8]) {
+        assert_eq!(run_length_encode(s), expect);
+        assert_eq!(run_length_decode(expect).unwrap(), s);
+    }
+
+    #[test]
+    fn test_run_length_round_trip() {
+        let mut samples = samples();
+        samples.push(vec![0; 1000]);
+        samples.push([vec![7; 257], vec![7; 258], vec![1]].concat());
+        for s in samples {
+            assert_eq!(run_length_decode(&run_length_encode(&s)).unwrap(), s);
+        }
+        assert_eq!(run_length_decode(b"aa"), None);
+    }
+
+    #[test]
+    fn test_compress() {
+        for s in samples() {
+            for &bijective in &[false, true] {
+                let data = compress(&s, bijective);
+                assert_eq!(decompress(&data).unwrap(), s);
+            }
+        }
+        let text = b"she sells sea shells by the sea shore ".repeat(50);
+        assert!(compress(&text, true).len() < text.len() / 4);
+        assert_eq!(decompress(b""), None);
+        assert_eq!(decompress(b"\x02abc"), None);
+        assert_eq!(decompress(b"\x00\x05\0\0\0\0\0\0\0abc"), None);
+    }
+}
@@ -0,0 +1,244 @@
+//! Closed-form counts of necklaces, Lyndon words, and bracelets.
+//!
+//! These grow exponentially, so they're computed exactly, without overflow.
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+/// The divisors of `n`, ascending.
+fn divisors(n: usize) -> Vec<usize> {
+    let mut low = Vec::new();
+    let mut high = Vec::new();
+    let mut d = 1;
+    while d * d <= n {
+        if n.is_multiple_of(d) {
+            low.push(d);
+            if d * d != n {
+                high.push(n / d);
+            }
+        }
+        d += 1;
+    }
+    low.extend(high.into_iter().rev());
+    low
+}
+
+/// The prime factors of `n`, without multiplicity.
+fn prime_factors(mut n: usize) -> Vec<usize> {
+    let mut primes = Vec::new();
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            primes.push(p);
+            while n.is_multiple_of(p) {
+                n /= p;
+            }
+        }
+        p += 1;
+    }
+    if n > 1 {
+        primes.push(n);
+    }
+    primes
+}
+
+/// Euler's totient: how many of `1..=n` are coprime to `n`.
+fn totient(n: usize) -> usize {
+    prime_factors(n)
+        .into_iter()
+        .fold(n, |phi, p| phi / p * (p - 1))
+}
+
+/// The Möbius function: 0 if `n` has a squared factor, else -1 to the number of its prime factors.
+fn mobius(n: usize) -> i8 {
+    let primes = prime_factors(n);
+    if primes.iter().product::<usize>() != n {
+        0
+    } else if primes.len().is_multiple_of(2) {
+        1
+    } else {
+        -1
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn pow(k: usize, exp: usize) -> BigUint {
+    BigUint::from(k).pow(exp as u32)
+}
+
+fn factorial(n: usize) -> BigUint {
+    (1..=n).fold(BigUint::one(), |f, i| f * i)
+}
+
+/// The number of necklaces of length `n` over `k` symbols.
+///
+/// By Burnside's lemma, this averages over the `n` rotations the number of
+/// words each fixes: `(1/n) Σ_{d|n} φ(d) k^(n/d)`.
+pub fn necklace_count(k: usize, n: usize) -> BigUint {
+    if n == 0 {
+        return BigUint::one();
+    }
+    let sum = divisors(n)
+        .into_iter()
+        .map(|d| pow(k, n / d) * totient(d))
+        .sum::<BigUint>();
+    sum / n
+}
+
+/// The number of Lyndon words of length `n` over `k` symbols.
+///
+/// By Möbius inversion: `(1/n) Σ_{d|n} μ(d) k^(n/d)`.
+pub fn lyndon_count(k: usize, n: usize) -> BigUint {
+    if n == 0 {
+        return BigUint::zero();
+    }
+    let mut positive = BigUint::zero();
+    let mut negative = BigUint::zero();
+    for d in divisors(n) {
+        match mobius(d) {
+            1 => positive += pow(k, n / d),
+            -1 => negative += pow(k, n / d),
+            _ => {}
+        }
+    }
+    (positive - negative) / n
+}
+
+/// The number of bracelets of length `n` over `k` symbols.
+///
+/// Burnside's lemma again, over the dihedral group: the `n` reflections add
+/// `k^((n+1)/2)` fixed words each when `n` is odd, and half of them
+/// `k^(n/2 + 1)` and the other half `k^(n/2)` when it's even.
+pub fn bracelet_count(k: usize, n: usize) -> BigUint {
+    if n == 0 {
+        return BigUint::one();
+    }
+    let necklaces = necklace_count(k, n);
+    if n % 2 == 1 {
+        (necklaces + pow(k, n.div_ceil(2))) / 2_u32
+    } else {
+        (necklaces * 2_u32 + pow(k, n / 2) * (k + 1)) / 4_u32
+    }
+}
+
+/// The number of necklaces with exactly `content[i]` copies of the `i`th symbol.
+///
+/// For example, `[2, 1]` counts the necklaces which are rotations of `aab`.
+/// A rotation by `n/d` fixes a word only if `d` divides every count, so this
+/// is `(1/n) Σ_{d|g} φ(d) (n/d)! / Π (content[i]/d)!`, where `g` is their gcd.
+pub fn fixed_content_necklace_count(content: &[usize]) -> BigUint {
+    let n = content.iter().sum::<usize>();
+    if n == 0 {
+        return BigUint::one();
+    }
+    let g = content.iter().fold(0, |g, &c| gcd(g, c));
+    let sum = divisors(g)
+        .into_iter()
+        .map(|d| {
+            let arrangements = content
+                .iter()
+                .fold(factorial(n / d), |a, &c| a / factorial(c / d));
+            arrangements * totient(d)
+        })
+        .sum::<BigUint>();
+    sum / n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enumerate::{lyndon_words, necklaces};
+    use crate::StringBracelet;
+    use rstest::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_number_theory() {
+        assert_eq!(divisors(12), vec![1, 2, 3, 4, 6, 12]);
+        assert_eq!(divisors(1), vec![1]);
+        assert_eq!(
+            (1..=10).map(totient).collect::<Vec<_>>(),
+            vec![1, 1, 2, 2, 4, 2, 6, 4, 6, 4]
+        );
+        assert_eq!(
+            (1..=10).map(mobius).collect::<Vec<_>>(),
+            vec![1, -1, -1, 0, -1, 1, -1, 0, 0, 1]
+        );
+    }
+
+    fn alphabet(k: usize) -> Vec<char> {
+        "abcde".chars().take(k).collect()
+    }
+
+    #[rstest(k, case(1), case(2), case(3), case(4))]
+    fn test_against_enumeration(k: usize) {
+        for n in 0..=7 {
+            let found: Vec<_> = necklaces(&alphabet(k), n).collect();
+            assert_eq!(necklace_count(k, n), found.len().into(), "k={} n={}", k, n);
+            assert_eq!(
+                lyndon_count(k, n),
+                lyndon_words(&alphabet(k), n).count().into(),
+                "k={} n={}",
+                k,
+                n
+            );
+            let bracelets = found
+                .iter()
+                .map(|n| StringBracelet::from_slice(n.as_slice()))
+                .collect::<HashSet<_>>();
+            assert_eq!(
+                bracelet_count(k, n),
+                bracelets.len().into(),
+                "k={} n={}",
+                k,
+                n
+            );
+        }
+    }
+
+    #[rstest(
+        content,
+        case(&[]),
+        case(&[1]),
+        case(&[2, 1]),
+        case(&[2, 2]),
+        case(&[3, 3]),
+        case(&[4, 2]),
+        case(&[2, 2, 2]),
+        case(&[3, 1, 2]),
+        case(&[0, 3])
+    )]
+    fn test_fixed_content(content: &[usize]) {
+        let k = content.len();
+        let n = content.iter().sum();
+        let found = necklaces(&alphabet(k), n)
+            .filter(|necklace| {
+                alphabet(k).iter().zip(content).all(|(symbol, &count)| {
+                    necklace.as_slice().iter().filter(|&c| c == symbol).count() == count
+                })
+            })
+            .count();
+        assert_eq!(fixed_content_necklace_count(content), found.into());
+    }
+
+    #[test]
+    fn test_large() {
+        // every word is some power of exactly one Lyndon word: Σ_{d|n} d L(d) = k^n
+        let (k, n) = (26, 360);
+        let words = divisors(n)
+            .into_iter()
+            .map(|d| lyndon_count(k, d) * d)
+            .sum::<BigUint>();
+        assert_eq!(words, pow(k, n));
+        assert!(necklace_count(k, n) > lyndon_count(k, n));
+        assert!(bracelet_count(k, n) * 2_u32 > necklace_count(k, n));
+        assert_eq!(necklace_count(2, 64).to_string(), "288230376218822676");
+    }
+}
@@ -0,0 +1,116 @@
+//! De Bruijn sequences: cyclic sequences which contain every word of some
+//! length exactly once.
+
+use crate::enumerate::{sorted, Fkm};
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+
+/// The de Bruijn sequence B(k, n) over `alphabet`, where `k` is its number of symbols.
+///
+/// Read cyclically, every word of length `n` over `alphabet` appears in it
+/// exactly once, so it has `k^n` symbols. It is the concatenation, in
+/// lexicographic order, of the Lyndon words whose length divides `n`, which
+/// makes it the lexicographically least such sequence.
+///
+/// For `n = 0` that is the least symbol alone: the empty word is the only
+/// word, and a single symbol has one empty window. No sequence exists for
+/// `n = 0` over an empty alphabet, so that returns an empty one.
+pub fn de_bruijn<T: Ord + Clone>(alphabet: &[T], n: usize) -> Vec<T> {
+    let mut alphabet = sorted(alphabet);
+    if n == 0 {
+        alphabet.truncate(1);
+        return alphabet;
+    }
+    let mut out = Vec::new();
+    let mut fkm = Fkm::new(alphabet.len(), n);
+    while let Some(p) = fkm.advance() {
+        if n.is_multiple_of(p) {
+            out.extend(fkm.prefix(p).iter().map(|&i| alphabet[i].clone()));
+        }
+    }
+    out
+}
+
+/// Whether `seq`, read cyclically, contains every word of length `n` over `alphabet` exactly once.
+pub fn is_de_bruijn<T: Ord>(seq: &[T], alphabet: &[T], n: usize) -> bool {
+    let symbols = alphabet.iter().collect::<BTreeSet<_>>();
+    let len: Option<usize> = u32::try_from(n)
+        .ok()
+        .and_then(|n| symbols.len().checked_pow(n));
+    if len != Some(seq.len()) || !seq.iter().all(|s| symbols.contains(s)) {
+        return false;
+    }
+    // as many distinct windows as there are words means every word is one of them
+    let windows = (0..seq.len())
+        .map(|start| {
+            (start..start + n)
+                .map(|i| &seq[i % seq.len()])
+                .collect::<Vec<_>>()
+        })
+        .collect::<BTreeSet<_>>();
+    windows.len() == seq.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[rstest(
+        alphabet,
+        n,
+        expect,
+        case("01", 0, "0"),
+        case("ba", 0, "a"),
+        case("01", 1, "01"),
+        case("01", 2, "0011"),
+        case("01", 3, "00010111"),
+        case("01", 4, "0000100110101111"),
+        case("012", 2, "001021122"),
+        case("10", 2, "0011"),
+        // '-' sorts before '.'
+        case(".-", 3, "---.-...")
+    )]
+    fn test_known(alphabet: &str, n: usize, expect: &str) {
+        let seq = de_bruijn(&chars(alphabet), n);
+        assert_eq!(seq.iter().collect::<String>(), expect);
+        assert!(is_de_bruijn(&seq, &chars(alphabet), n));
+    }
+
+    #[test]
+    fn test_verify_generated() {
+        for k in 1..=4 {
+            let alphabet = (0..k).collect::<Vec<u8>>();
+            for n in 0..=5 {
+                let seq = de_bruijn(&alphabet, n);
+                assert_eq!(seq.len(), (k as usize).pow(n as u32));
+                assert!(is_de_bruijn(&seq, &alphabet, n), "k={} n={}", k, n);
+            }
+        }
+    }
+
+    #[rstest(
+        seq,
+        n,
+        case("0101", 2),
+        case("001", 2),
+        case("00112", 2),
+        case("0012", 2),
+        case("00110", 2)
+    )]
+    fn test_reject(seq: &str, n: usize) {
+        assert!(!is_de_bruijn(&chars(seq), &chars("01"), n));
+    }
+
+    #[test]
+    fn test_rotations() {
+        // any rotation of a de Bruijn sequence is one too
+        let mut seq = de_bruijn(&chars("abc"), 3);
+        seq.rotate_left(10);
+        assert!(is_de_bruijn(&seq, &chars("abc"), 3));
+    }
+}
@@ -0,0 +1,227 @@
+//! Enumeration of necklaces, Lyndon words, and prenecklaces by the
+//! Fredricksen–Kessler–Maiorana algorithm.
+//!
+//! Every prenecklace of length `n` is visited in lexicographic order, each in
+//! constant amortized time. A prenecklace is a necklace when its period `p`
+//! divides `n`, and a Lyndon word when `p == n`; both filters also run in
+//! constant amortized time.
+
+use crate::Necklace;
+
+/// The state of the FKM algorithm: the current prenecklace, as indices into an alphabet.
+#[derive(Debug, Clone)]
+pub(crate) struct Fkm {
+    /// `a[0]` is a sentinel; the prenecklace is `a[1..]`
+    a: Vec<usize>,
+    k: usize,
+    /// the length of the longest Lyndon prefix of the current prenecklace
+    p: usize,
+    started: bool,
+}
+
+impl Fkm {
+    pub(crate) fn new(k: usize, n: usize) -> Fkm {
+        Fkm {
+            a: vec![0; n + 1],
+            k,
+            p: 1,
+            started: false,
+        }
+    }
+
+    pub(crate) fn n(&self) -> usize {
+        self.a.len() - 1
+    }
+
+    /// Step to the next prenecklace, returning its period, or `None` once they're exhausted.
+    pub(crate) fn advance(&mut self) -> Option<usize> {
+        let n = self.n();
+        if !self.started {
+            self.started = true;
+            if self.k == 0 && n > 0 {
+                return None;
+            }
+            // the empty word has no positions to fill, and is its own necklace
+            self.p = n.min(1);
+            return Some(self.p);
+        }
+        let mut i = n;
+        while i > 0 && self.a[i] == self.k - 1 {
+            i -= 1;
+        }
+        if i == 0 {
+            return None;
+        }
+        self.a[i] += 1;
+        for j in i + 1..=n {
+            self.a[j] = self.a[j - i];
+        }
+        self.p = i;
+        Some(i)
+    }
+
+    /// The first `len` symbols of the current prenecklace, as indices.
+    pub(crate) fn prefix(&self, len: usize) -> &[usize] {
+        &self.a[1..=len]
+    }
+
+    fn word<T: Clone>(&self, alphabet: &[T]) -> Vec<T> {
+        self.a[1..].iter().map(|&i| alphabet[i].clone()).collect()
+    }
+}
+
+/// The symbols of an alphabet in order, without repeats, so that FKM's order is theirs.
+pub(crate) fn sorted<T: Ord + Clone>(alphabet: &[T]) -> Vec<T> {
+    let mut alphabet = alphabet.to_vec();
+    alphabet.sort();
+    alphabet.dedup();
+    alphabet
+}
+
+/// An iterator over the necklaces or Lyndon words of one length, in lexicographic order.
+#[derive(Debug, Clone)]
+pub struct Necklaces<T> {
+    alphabet: Vec<T>,
+    fkm: Fkm,
+    lyndon: bool,
+}
+
+impl<T: Ord + Clone> Iterator for Necklaces<T> {
+    type Item = Necklace<T>;
+
+    fn next(&mut self) -> Option<Necklace<T>> {
+        let n = self.fkm.n();
+        loop {
+            let p = self.fkm.advance()?;
+            let wanted = if self.lyndon {
+                p == n && n > 0
+            } else {
+                n.is_multiple_of(p)
+            };
+            if wanted {
+                // FKM visits each necklace as its least rotation
+                return Some(self.fkm.word(&self.alphabet).into());
+            }
+        }
+    }
+}
+
+/// An iterator over the prenecklaces of one length, in lexicographic order.
+///
+/// A prenecklace is a prefix of some necklace, so it needn't be a least
+/// rotation itself: `aba` is a prenecklace, as a prefix of `abab`.
+#[derive(Debug, Clone)]
+pub struct Prenecklaces<T> {
+    alphabet: Vec<T>,
+    fkm: Fkm,
+}
+
+impl<T: Ord + Clone> Iterator for Prenecklaces<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        self.fkm.advance()?;
+        Some(self.fkm.word(&self.alphabet))
+    }
+}
+
+/// Every necklace of length `n` over `alphabet`.
+pub fn necklaces<T: Ord + Clone>(alphabet: &[T], n: usize) -> Necklaces<T> {
+    let alphabet = sorted(alphabet);
+    Necklaces {
+        fkm: Fkm::new(alphabet.len(), n),
+        alphabet,
+        lyndon: false,
+    }
+}
+
+/// Every Lyndon word of length `n` over `alphabet`: the necklaces which equal none of their rotations.
+pub fn lyndon_words<T: Ord + Clone>(alphabet: &[T], n: usize) -> Necklaces<T> {
+    Necklaces {
+        lyndon: true,
+        ..necklaces(alphabet, n)
+    }
+}
+
+/// Every prenecklace of length `n` over `alphabet`.
+pub fn prenecklaces<T: Ord + Clone>(alphabet: &[T], n: usize) -> Prenecklaces<T> {
+    let alphabet = sorted(alphabet);
+    Prenecklaces {
+        fkm: Fkm::new(alphabet.len(), n),
+        alphabet,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StringNecklace;
+    use rstest::*;
+    use std::collections::BTreeSet;
+
+    /// Every word of length `n` over `alphabet`.
+    fn words(alphabet: &[char], n: usize) -> Vec<String> {
+        (0..n).fold(vec![String::new()], |words, _| {
+            words
+                .iter()
+                .flat_map(|w| alphabet.iter().map(move |c| format!("{}{}", w, c)))
+                .collect()
+        })
+    }
+
+    #[rstest(
+        k,
+        n,
+        necklace_count,
+        lyndon_count,
+        case(2, 0, 1, 0),
+        case(2, 1, 2, 2),
+        case(2, 4, 6, 3),
+        case(2, 6, 14, 9),
+        case(3, 4, 24, 18),
+        case(4, 3, 24, 20),
+        case(1, 5, 1, 0),
+        case(0, 3, 0, 0)
+    )]
+    fn test_counts(k: usize, n: usize, necklace_count: usize, lyndon_count: usize) {
+        let alphabet: Vec<char> = "abcdefgh".chars().take(k).collect();
+        assert_eq!(necklaces(&alphabet, n).count(), necklace_count);
+        assert_eq!(lyndon_words(&alphabet, n).count(), lyndon_count);
+    }
+
+    #[rstest(k, n, case(2, 5), case(2, 6), case(3, 4), case(4, 3))]
+    fn test_brute_force(k: usize, n: usize) {
+        let alphabet: Vec<char> = "abcd".chars().take(k).collect();
+        let all = words(&alphabet, n);
+
+        let expect: BTreeSet<StringNecklace> = all.iter().map(StringNecklace::new).collect();
+        let found: Vec<StringNecklace> = necklaces(&alphabet, n).collect();
+        assert!(found.windows(2).all(|w| w[0] < w[1]), "not in order");
+        assert_eq!(found.into_iter().collect::<BTreeSet<_>>(), expect);
+
+        let lyndon: Vec<StringNecklace> = expect.into_iter().filter(|n| n.repeats() == 1).collect();
+        assert_eq!(lyndon_words(&alphabet, n).collect::<Vec<_>>(), lyndon);
+
+        // prenecklaces are the prefixes of the necklaces twice as long
+        let expect: BTreeSet<String> = necklaces(&alphabet, 2 * n)
+            .map(|necklace| necklace.as_slice()[..n].iter().collect())
+            .collect();
+        let found: Vec<String> = prenecklaces(&alphabet, n)
+            .map(|p| p.into_iter().collect())
+            .collect();
+        assert_eq!(found.iter().cloned().collect::<BTreeSet<_>>(), expect);
+        assert_eq!(found.len(), expect.len());
+    }
+
+    #[test]
+    fn test_alphabet() {
+        let found: Vec<String> = necklaces(&['-', '.', '-'], 3)
+            .map(|n| n.to_string())
+            .collect();
+        assert_eq!(found, vec!["---", "--.", "-..", "..."]);
+        let words: Vec<Vec<&str>> = lyndon_words(&["up", "down"], 2)
+            .map(|n| n.as_slice().to_vec())
+            .collect();
+        assert_eq!(words, vec![vec!["down", "up"]]);
+    }
+}
@@ -1,44 +1,356 @@
-use std::collections::VecDeque;
+mod bracelet;
+pub mod bwt;
+pub mod count;
+pub mod de_bruijn;
+pub mod enumerate;
+pub mod lyndon;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct StringNecklace(VecDeque<char>);
+pub use bracelet::{Bracelet, StringBracelet};
 
-impl StringNecklace {
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::ops::Range;
+
+/// The offset of the lexicographically least rotation of `s`.
+///
+/// This is Duval's Lyndon factorization run over `s` doubled: the least
+/// rotation starts at the last factor to begin within the first copy. It takes
+/// O(n) time and no allocation. When `s` is periodic, several offsets give the
+/// least rotation; this returns the first.
+pub fn least_rotation<T: Ord>(s: &[T]) -> usize {
+    least_rotation_from(s, 0)
+}
+
+/// `least_rotation`, with Duval's algorithm over `s` doubled resumed at `start`,
+/// which must be where some run of factors of the doubled sequence starts.
+fn least_rotation_from<T: Ord>(s: &[T], start: usize) -> usize {
+    let n = s.len();
+    let mut least = start;
+    lyndon::duval_runs(|i| &s[i % n], 2 * n, start, n, |run| least = run.start);
+    least
+}
+
+/// The smallest `p` such that `s[i] == s[i + p]` wherever both exist.
+///
+/// This comes from the KMP failure function: the longest proper border of `s`
+/// leaves `s.len() - border` as its period. It needn't divide `s.len()`; `abcab`
+/// has period 3.
+pub fn smallest_period<T: PartialEq>(s: &[T]) -> usize {
+    let mut fail = vec![0; s.len() + 1];
+    for i in 1..s.len() {
+        let mut k = fail[i];
+        while k > 0 && s[i] != s[k] {
+            k = fail[k];
+        }
+        if s[i] == s[k] {
+            k += 1;
+        }
+        fail[i + 1] = k;
+    }
+    s.len() - fail[s.len()]
+}
+
+/// The smallest rotation which leaves `s` unchanged, or `s.len()` if only a full turn does.
+///
+/// If some rotation `p` does, `s` repeats with period `p`, and by the
+/// Fine–Wilf theorem no smaller period can fail to divide its length, so this
+/// is `smallest_period` whenever that divides the length.
+fn rotational_period<T: PartialEq>(s: &[T]) -> usize {
+    let p = smallest_period(s);
+    if s.len().is_multiple_of(p) {
+        p
+    } else {
+        s.len()
+    }
+}
+
+/// Every `k` for which rotating `a` left by `k` gives `b`, ascending, or
+/// `None` if `b` isn't a rotation of `a`.
+///
+/// There's more than one when `a` is periodic: `abab` becomes itself rotated
+/// by 0 or 2.
+pub fn rotation_between<T: Ord>(a: &[T], b: &[T]) -> Option<Vec<usize>> {
+    let n = a.len();
+    if n != b.len() {
+        return None;
+    }
+    if n == 0 {
+        return Some(vec![0]);
+    }
+    let (na, nb) = (NecklaceRef::new(a), NecklaceRef::new(b));
+    if na != nb {
+        return None;
+    }
+    // both reach the least rotation, so `a` reaches `b` by going there and back
+    let first = (na.offset() + n - nb.offset()) % n;
+    let period = rotational_period(a);
+    let mut shifts: Vec<usize> = (0..n / period).map(|m| (first + m * period) % n).collect();
+    shifts.sort_unstable();
+    Some(shifts)
+}
+
+/// A sequence in which every rotation is equivalent, like beads on a necklace.
+///
+/// It's stored as its least rotation, so that equivalent sequences compare and
+/// hash equal, along with how far the sequence it was built from was rotated
+/// to reach it.
+#[derive(Debug, Clone)]
+pub struct Necklace<T> {
+    items: Vec<T>,
+    offset: usize,
+}
+
+/// A necklace of characters.
+pub type StringNecklace = Necklace<char>;
+
+impl Necklace<char> {
     pub fn new<T>(s: T) -> StringNecklace
     where
         T: AsRef<str>,
     {
-        let mut d: VecDeque<char> = s.as_ref().chars().collect();
-        if !d.is_empty() {
-            d = (0..d.len())
-                .map(|shift| {
-                    let mut c = d.clone();
-                    c.rotate_left(shift);
-                    c
-                })
-                .min()
-                .unwrap();
-        }
-        StringNecklace(d)
+        s.as_ref().chars().collect()
+    }
+
+    /// Like `new`, but also return the Lyndon factorization of `s` from which
+    /// the necklace was found; see `Necklace::with_factorization`.
+    pub fn with_str_factorization(s: &str) -> (StringNecklace, Vec<&str>) {
+        let (necklace, factors) = Necklace::factored(s.chars().collect());
+        (necklace, lyndon::char_slices(s, factors))
+    }
+}
+
+impl<T: Ord> Necklace<T> {
+    pub fn from_slice(items: &[T]) -> Necklace<T>
+    where
+        T: Clone,
+    {
+        items.to_vec().into()
+    }
+
+    /// Like `from_slice`, but also return the Lyndon factorization of `items`.
+    ///
+    /// The least rotation is found from this factorization. Each run of equal
+    /// factors which Duval's algorithm closed before reaching the end of
+    /// `items` is a run of `items` doubled as well, so the search for the least
+    /// rotation over the doubled sequence picks up after them.
+    pub fn with_factorization(items: &[T]) -> (Necklace<T>, Vec<&[T]>)
+    where
+        T: Clone,
+    {
+        let (necklace, factors) = Necklace::factored(items.to_vec());
+        (necklace, factors.into_iter().map(|r| &items[r]).collect())
+    }
+
+    fn factored(mut items: Vec<T>) -> (Necklace<T>, Vec<Range<usize>>) {
+        let mut factors = Vec::new();
+        // the last run always reads to the end
+        let mut resume = items.len();
+        lyndon::duval_runs(
+            |i| &items[i],
+            items.len(),
+            0,
+            items.len(),
+            |run| {
+                if run.at_end {
+                    resume = resume.min(run.start);
+                }
+                factors.extend(run.factors());
+            },
+        );
+        let offset = least_rotation_from(&items, resume);
+        items.rotate_left(offset);
+        (Necklace { items, offset }, factors)
     }
 
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.items.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The least rotation.
+    pub fn as_slice(&self) -> &[T] {
+        &self.items
+    }
+
+    /// How far left the sequence this was built from was rotated to give its least rotation.
+    ///
+    /// When the necklace is periodic, adding any multiple of its period gives
+    /// the same rotation; this is the smallest such offset.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The smallest rotation which leaves this necklace unchanged.
+    ///
+    /// This is the length of its primitive root, or 0 for the empty necklace.
+    pub fn period(&self) -> usize {
+        rotational_period(&self.items)
+    }
+
+    /// The shortest sequence which, repeated, produces this necklace.
+    pub fn primitive_root(&self) -> &[T] {
+        &self.items[..self.period()]
+    }
+
+    /// How many rotations produce this same sequence.
     pub fn repeats(&self) -> usize {
-        if self.0.is_empty() {
+        if self.items.is_empty() {
             return 1;
         }
-        let mut count = 0;
-        let mut m = self.clone();
-        for _ in 0..self.len() {
-            m.0.rotate_left(1);
-            if &m == self {
-                count += 1;
-            }
+        self.len() / self.period()
+    }
+
+    /// Each distinct rotation, starting from the least and rotating left one step at a time.
+    ///
+    /// There are as many as the period, since after that they repeat.
+    pub fn rotations(&self) -> impl Iterator<Item = Vec<T>> + '_
+    where
+        T: Clone,
+    {
+        (0..self.period().max(1)).map(move |k| {
+            let (head, tail) = self.items.split_at(k);
+            [tail, head].concat()
+        })
+    }
+}
+
+impl<T: Ord> From<Vec<T>> for Necklace<T> {
+    fn from(mut items: Vec<T>) -> Necklace<T> {
+        let offset = least_rotation(&items);
+        items.rotate_left(offset);
+        Necklace { items, offset }
+    }
+}
+
+impl<T: PartialEq> PartialEq for Necklace<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items
+    }
+}
+
+impl<T: Eq> Eq for Necklace<T> {}
+
+impl<T: PartialOrd> PartialOrd for Necklace<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.items.partial_cmp(&other.items)
+    }
+}
+
+impl<T: Ord> Ord for Necklace<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.items.cmp(&other.items)
+    }
+}
+
+impl<T: Hash> Hash for Necklace<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.items.hash(state);
+    }
+}
+
+impl<T: Ord> FromIterator<T> for Necklace<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Necklace<T> {
+        iter.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+impl fmt::Display for StringNecklace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.items.iter().try_for_each(|c| write!(f, "{}", c))
+    }
+}
+
+/// A necklace over borrowed data, which never copies it.
+///
+/// It keeps the source slice and the offset of its least rotation, and
+/// compares, orders, and hashes as that rotation, so equivalent slices are
+/// equal. Hashing agrees with equality, but not with `Necklace`'s hash.
+#[derive(Debug, Clone, Copy)]
+pub struct NecklaceRef<'a, T> {
+    items: &'a [T],
+    offset: usize,
+}
+
+impl<'a, T: Ord> NecklaceRef<'a, T> {
+    pub fn new(items: &'a [T]) -> NecklaceRef<'a, T> {
+        NecklaceRef {
+            items,
+            offset: least_rotation(items),
         }
-        count
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// How far left `items` is rotated to give the least rotation.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The items of the least rotation, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + Clone {
+        let (head, tail) = self.items.split_at(self.offset);
+        tail.iter().chain(head)
+    }
+
+    /// Copy the least rotation into an owned `Necklace`.
+    pub fn to_necklace(&self) -> Necklace<T>
+    where
+        T: Clone,
+    {
+        Necklace {
+            items: self.iter().cloned().collect(),
+            offset: self.offset,
+        }
+    }
+}
+
+/// A string's necklace, over its bytes.
+///
+/// UTF-8 orders like the characters it encodes, and valid strings can only be
+/// rotations of one another at character boundaries, so this groups strings
+/// just as `StringNecklace` does, but its least rotation may start
+/// mid-character.
+impl<'a> From<&'a str> for NecklaceRef<'a, u8> {
+    fn from(s: &'a str) -> NecklaceRef<'a, u8> {
+        NecklaceRef::new(s.as_bytes())
+    }
+}
+
+impl<'a, T: Ord> PartialEq for NecklaceRef<'a, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<'a, T: Ord> Eq for NecklaceRef<'a, T> {}
+
+impl<'a, T: Ord> PartialOrd for NecklaceRef<'a, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, T: Ord> Ord for NecklaceRef<'a, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<'a, T: Ord + Hash> Hash for NecklaceRef<'a, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        self.iter().for_each(|item| item.hash(state));
     }
 }
 
@@ -46,6 +358,7 @@ impl StringNecklace {
 mod tests {
     use super::*;
     use rstest::*;
+    use std::cell::Cell;
 
     #[rstest(
         a,
@@ -84,4 +397,345 @@ mod tests {
         let n = StringNecklace::new(s);
         assert_eq!(n.repeats(), expect);
     }
+
+    #[rstest(
+        s,
+        period,
+        root,
+        case("", 0, ""),
+        case("a", 1, "a"),
+        case("aaaaaa", 1, "a"),
+        case("abcabcabc", 3, "abc"),
+        case("cabcabcab", 3, "abc"),
+        case("abcabcabcx", 10, "abcabcabcx"),
+        case("abab", 2, "ab"),
+        case("abaab", 5, "aabab")
+    )]
+    fn test_period(s: &str, period: usize, root: &str) {
+        let n = StringNecklace::new(s);
+        assert_eq!(n.period(), period);
+        assert_eq!(n.primitive_root().iter().collect::<String>(), root);
+        assert_eq!(StringNecklace::new(root.repeat(n.repeats())), n);
+    }
+
+    #[rstest(
+        s,
+        expect,
+        case("", 0),
+        case("abcab", 3),
+        case("aabaab", 3),
+        case("abcd", 4),
+        case("aaaa", 1)
+    )]
+    fn test_smallest_period(s: &str, expect: usize) {
+        let chars: Vec<char> = s.chars().collect();
+        assert_eq!(smallest_period(&chars), expect);
+    }
+
+    #[test]
+    fn test_generic() {
+        assert_eq!(
+            Necklace::from_slice(b"nicole"),
+            Necklace::from_slice(b"lenico")
+        );
+        assert_eq!(
+            Necklace::from(vec![3, 1, 2, 3, 1, 2]).as_slice(),
+            &[1, 2, 3, 1, 2, 3]
+        );
+        assert_eq!(Necklace::from(vec![3, 1, 2, 3, 1, 2]).repeats(), 2);
+
+        fn sentence(s: &str) -> Necklace<&str> {
+            s.split(' ').collect()
+        }
+        assert_eq!(
+            sentence("the cat sat on the mat"),
+            sentence("on the mat the cat sat")
+        );
+        assert_ne!(
+            sentence("the cat sat on the mat"),
+            sentence("the mat sat on the cat")
+        );
+        assert_eq!(sentence("a b a b").primitive_root(), &["a", "b"]);
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        enum Bead {
+            Red,
+            Blue,
+        }
+        use Bead::*;
+        assert_eq!(
+            Necklace::from_slice(&[Blue, Red, Red]).as_slice(),
+            &[Red, Red, Blue]
+        );
+    }
+
+    #[rstest(
+        a,
+        b,
+        expect_equal,
+        case("nicole", "icolen", true),
+        case("nicole", "coneli", false),
+        case("aabaaaaabaab", "aabaabaabaaa", true),
+        case("abc", "cba", false),
+        case("x", "xx", false),
+        case("", "", true),
+        case("ñandú", "úñand", true),
+        case("éé", "éè", false)
+    )]
+    fn test_ref(a: &str, b: &str, expect_equal: bool) {
+        use std::collections::hash_map::DefaultHasher;
+        let hash = |n: &NecklaceRef<u8>| {
+            let mut h = DefaultHasher::new();
+            n.hash(&mut h);
+            h.finish()
+        };
+        let (ra, rb) = (NecklaceRef::from(a), NecklaceRef::from(b));
+        assert_eq!(ra == rb, expect_equal);
+        if expect_equal {
+            assert_eq!(hash(&ra), hash(&rb));
+        }
+        // ordered like the owned necklaces, which materialize the rotation
+        assert_eq!(ra.cmp(&rb), ra.to_necklace().cmp(&rb.to_necklace()));
+    }
+
+    #[test]
+    fn test_ref_iter() {
+        let items = [3, 1, 2];
+        let n = NecklaceRef::new(&items);
+        assert_eq!(n.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(n.to_necklace(), Necklace::from(vec![2, 3, 1]));
+    }
+
+    #[test]
+    fn test_with_factorization() {
+        let (necklace, factors) = StringNecklace::with_str_factorization("banana");
+        assert_eq!(factors, vec!["b", "an", "an", "a"]);
+        assert_eq!(necklace.to_string(), "abanan");
+        // the least rotation starts at the last factor
+        assert_eq!(factors[3..].concat() + &factors[..3].concat(), "abanan");
+
+        // here the least rotation starts at neither the first factor nor the last
+        let (necklace, factors) = Necklace::with_factorization(&[2, 1, 2, 1]);
+        assert_eq!(factors, vec![&[2][..], &[1, 2], &[1]]);
+        assert_eq!(necklace.as_slice(), &[1, 2, 1, 2]);
+        assert_eq!(necklace.offset(), 1);
+    }
+
+    #[test]
+    fn test_with_factorization_agrees() {
+        // every word of up to 10 letters over a 3-letter alphabet
+        for len in 0..=10 {
+            for code in 0..3_usize.pow(len) {
+                let items: Vec<u8> = (0..len)
+                    .map(|i| (code / 3_usize.pow(i) % 3) as u8)
+                    .collect();
+                let (necklace, factors) = Necklace::with_factorization(&items);
+                let plain = Necklace::from_slice(&items);
+                assert_eq!(necklace.as_slice(), plain.as_slice(), "{:?}", items);
+                assert_eq!(necklace.offset(), plain.offset(), "{:?}", items);
+                assert_eq!(factors, lyndon::lyndon_factors(&items));
+            }
+        }
+    }
+
+    #[rstest(
+        s,
+        offset,
+        case("", 0),
+        case("nicole", 2),
+        case("lenico", 4),
+        case("abab", 0),
+        case("baba", 1),
+        case("cabcab", 1)
+    )]
+    fn test_offset(s: &str, offset: usize) {
+        let n = StringNecklace::new(s);
+        assert_eq!(n.offset(), offset);
+        let chars: Vec<char> = s.chars().collect();
+        assert_eq!(NecklaceRef::new(&chars).offset(), offset);
+        let mut rotated = chars.clone();
+        rotated.rotate_left(offset);
+        assert_eq!(rotated, n.as_slice());
+        // the offset doesn't change which necklace it is
+        assert_eq!(n, StringNecklace::new(n.to_string()));
+    }
+
+    #[rstest(
+        a,
+        b,
+        expect,
+        case("nicole", "icolen", Some(vec![1])),
+        case("nicole", "lenico", Some(vec![4])),
+        case("nicole", "nicole", Some(vec![0])),
+        case("abab", "baba", Some(vec![1, 3])),
+        case("abcabcabc", "cabcabcab", Some(vec![2, 5, 8])),
+        case("aaa", "aaa", Some(vec![0, 1, 2])),
+        case("", "", Some(vec![0])),
+        case("abc", "cba", None),
+        case("x", "xx", None)
+    )]
+    fn test_rotation_between(a: &str, b: &str, expect: Option<Vec<usize>>) {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        let found = rotation_between(&a, &b);
+        assert_eq!(found, expect);
+        // check against every rotation
+        let brute: Vec<usize> = (0..a.len().max(1))
+            .filter(|&k| {
+                let mut r = a.clone();
+                r.rotate_left(k.min(a.len()));
+                r == b
+            })
+            .collect();
+        assert_eq!(found.unwrap_or_default(), brute);
+    }
+
+    #[rstest(
+        s,
+        expect,
+        case("", &[""]),
+        case("a", &["a"]),
+        case("cab", &["abc", "bca", "cab"]),
+        case("abab", &["abab", "baba"]),
+        case("aaaa", &["aaaa"])
+    )]
+    fn test_rotations(s: &str, expect: &[&str]) {
+        let rotations: Vec<String> = StringNecklace::new(s)
+            .rotations()
+            .map(|r| r.into_iter().collect())
+            .collect();
+        assert_eq!(rotations, expect);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(StringNecklace::new("lenico").to_string(), "coleni");
+    }
+
+    /// The least rotation by brute force, as `StringNecklace::new` used to find it.
+    fn naive_least_rotation(s: &str) -> String {
+        let chars: Vec<char> = s.chars().collect();
+        (0..chars.len().max(1))
+            .map(|shift| {
+                let mut c = chars.clone();
+                c.rotate_left(shift.min(chars.len()));
+                c.into_iter().collect::<String>()
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[rstest(
+        s,
+        case(""),
+        case("a"),
+        case("ba"),
+        case("nicole"),
+        case("aabaaaaabaab"),
+        case("abcabcabc"),
+        case("bbbbbba"),
+        case("abababab"),
+        case("dcbadcbadcba"),
+        case("cabcab"),
+        case("ñandú")
+    )]
+    fn test_least_rotation(s: &str) {
+        let n = StringNecklace::new(s);
+        assert_eq!(n.items.iter().collect::<String>(), naive_least_rotation(s));
+    }
+
+    /// A pseudorandom string of `len` characters from `alphabet`.
+    fn pseudorandom(len: usize, alphabet: &[char]) -> Vec<char> {
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                alphabet[(state % alphabet.len() as u64) as usize]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_million_chars() {
+        // rotating every one of these by brute force would take about 10^12 comparisons
+        const LEN: usize = 1_000_000;
+        let mut chars = pseudorandom(LEN, &['a', 'b']);
+        // plant a run of a's longer than any which could occur by chance
+        let planted = 700_000;
+        chars[planted - 1] = 'b';
+        for c in &mut chars[planted..planted + 64] {
+            *c = 'a';
+        }
+        let s: String = chars.iter().collect();
+
+        let n = StringNecklace::new(&s);
+        assert_eq!(n.len(), LEN);
+        assert_eq!(least_rotation(&chars), planted);
+        let mut expect = chars.clone();
+        expect.rotate_left(planted);
+        assert!(n.items.iter().eq(expect.iter()));
+
+        // the worst case for naive comparison: every rotation shares a long prefix
+        let mut runs = vec!['a'; LEN];
+        runs[LEN / 3] = 'b';
+        assert_eq!(least_rotation(&runs), LEN / 3 + 1);
+    }
+
+    /// An item which counts how often it is compared.
+    struct Counted<'c> {
+        item: char,
+        comparisons: &'c Cell<usize>,
+    }
+
+    impl<'c> PartialEq for Counted<'c> {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Ordering::Equal
+        }
+    }
+
+    impl<'c> Eq for Counted<'c> {}
+
+    impl<'c> PartialOrd for Counted<'c> {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl<'c> Ord for Counted<'c> {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.comparisons.set(self.comparisons.get() + 1);
+            self.item.cmp(&other.item)
+        }
+    }
+
+    #[rstest(len, case(1_000), case(10_000), case(100_000), case(1_000_000))]
+    fn test_least_rotation_linear(len: usize) {
+        let mut runs = vec!['a'; len];
+        runs[len / 3] = 'b';
+        let mut planted = pseudorandom(len, &['a', 'b']);
+        for c in &mut planted[len / 2..len / 2 + 32] {
+            *c = 'a';
+        }
+        for s in &[runs, planted, pseudorandom(len, &['a', 'b', 'c'])] {
+            let comparisons = Cell::new(0);
+            let counted: Vec<Counted> = s
+                .iter()
+                .map(|&item| Counted {
+                    item,
+                    comparisons: &comparisons,
+                })
+                .collect();
+            least_rotation(&counted);
+            // this takes under five comparisons per item, where comparing
+            // rotations naively would take hundreds even at the smallest size
+            assert!(
+                comparisons.get() <= 8 * len,
+                "{} comparisons for {} items",
+                comparisons.get(),
+                len
+            );
+        }
+    }
 }
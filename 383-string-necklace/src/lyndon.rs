@@ -0,0 +1,233 @@
+//! Lyndon words, and the factorization of any word into them.
+//!
+//! A Lyndon word is strictly less than each of its proper rotations. Every
+//! word is uniquely a concatenation of non-increasing Lyndon words (the
+//! Chen–Fox–Lyndon theorem), and Duval's algorithm finds them in linear time.
+
+use std::ops::Range;
+
+/// A run of equal Lyndon factors, as Duval's algorithm finds them.
+pub(crate) struct Run {
+    pub start: usize,
+    /// the length of each factor
+    pub len: usize,
+    /// how many times the factor repeats
+    pub count: usize,
+    /// whether the scan which found this run read to the end of the input,
+    /// rather than stopping at a smaller item
+    pub at_end: bool,
+}
+
+impl Run {
+    /// The range of each factor in the run.
+    pub fn factors(&self) -> impl Iterator<Item = Range<usize>> {
+        let (start, len) = (self.start, self.len);
+        (0..self.count).map(move |m| start + m * len..start + (m + 1) * len)
+    }
+}
+
+/// Duval's algorithm over the `len` items which `at` reads, from `start`.
+///
+/// Each run of equal factors goes to `found`, until a run would start at or
+/// after `stop`. Once one scan reads to the end of the input, every later one
+/// does too, since what remains is a prefix of the factor just found.
+pub(crate) fn duval_runs<'s, T, A, F>(at: A, len: usize, start: usize, stop: usize, mut found: F)
+where
+    T: Ord + 's,
+    A: Fn(usize) -> &'s T,
+    F: FnMut(Run),
+{
+    let mut i = start;
+    while i < stop.min(len) {
+        let mut j = i + 1;
+        let mut k = i;
+        while j < len && at(k) <= at(j) {
+            if at(k) < at(j) {
+                k = i;
+            } else {
+                k += 1;
+            }
+            j += 1;
+        }
+        let run = Run {
+            start: i,
+            len: j - k,
+            count: (k - i) / (j - k) + 1,
+            at_end: j == len,
+        };
+        i += run.len * run.count;
+        found(run);
+    }
+}
+
+/// The ranges of the Lyndon factors of `s`, in order.
+fn duval<T: Ord>(s: &[T]) -> Vec<Range<usize>> {
+    let mut factors = Vec::new();
+    duval_runs(
+        |i| &s[i],
+        s.len(),
+        0,
+        s.len(),
+        |run| factors.extend(run.factors()),
+    );
+    factors
+}
+
+/// The Lyndon factorization of any ordered sequence.
+pub fn lyndon_factors<T: Ord>(s: &[T]) -> Vec<&[T]> {
+    duval(s).into_iter().map(|r| &s[r]).collect()
+}
+
+/// The Lyndon factorization of `s`: the unique non-increasing sequence of
+/// Lyndon words which concatenate to it.
+///
+/// `banana` factors as `b`, `an`, `an`, `a`.
+pub fn lyndon_factorization(s: &str) -> Vec<&str> {
+    let chars: Vec<char> = s.chars().collect();
+    char_slices(s, duval(&chars))
+}
+
+/// The slices of `s` which `ranges` of its characters cover.
+pub(crate) fn char_slices(s: &str, ranges: Vec<Range<usize>>) -> Vec<&str> {
+    let bytes: Vec<usize> = s
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(Some(s.len()))
+        .collect();
+    ranges
+        .into_iter()
+        .map(|r| &s[bytes[r.start]..bytes[r.end]])
+        .collect()
+}
+
+/// Whether `s` is a Lyndon word: nonempty, and strictly less than all its proper rotations.
+pub fn is_lyndon(s: &str) -> bool {
+    lyndon_factorization(s).len() == 1
+}
+
+/// Whether `s` is a necklace: no rotation of it is less than it.
+///
+/// That makes it some Lyndon word repeated, so all its factors are equal.
+pub fn is_necklace(s: &str) -> bool {
+    let factors = lyndon_factorization(s);
+    factors.iter().all(|f| *f == factors[0])
+}
+
+/// Whether `s` is a prenecklace: a prefix of some necklace.
+///
+/// Exactly these are some Lyndon word repeated, then a prefix of it, which
+/// Duval's algorithm recognizes when its first scan reads to the end of `s`.
+pub fn is_prenecklace(s: &str) -> bool {
+    let s: Vec<char> = s.chars().collect();
+    let mut prenecklace = true;
+    duval_runs(|i| &s[i], s.len(), 0, 1, |run| prenecklace = run.at_end);
+    prenecklace
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enumerate::{lyndon_words, necklaces, prenecklaces};
+    use crate::least_rotation;
+    use rstest::*;
+
+    #[rstest(
+        s,
+        expect,
+        case("", &[]),
+        case("a", &["a"]),
+        case("banana", &["b", "an", "an", "a"]),
+        case("aabaaaaabaab", &["aab", "aaaaabaab"]),
+        case("abab", &["ab", "ab"]),
+        case("cba", &["c", "b", "a"]),
+        case("ñandú", &["ñ", "andú"])
+    )]
+    fn test_factorization(s: &str, expect: &[&str]) {
+        let factors = lyndon_factorization(s);
+        assert_eq!(factors, expect);
+        assert_eq!(factors.concat(), s);
+        assert!(factors.iter().all(|f| is_lyndon(f)));
+        assert!(factors.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[rstest(
+        s,
+        lyndon,
+        necklace,
+        prenecklace,
+        case("", false, true, true),
+        case("a", true, true, true),
+        case("ab", true, true, true),
+        case("ba", false, false, false),
+        case("abab", false, true, true),
+        case("aba", false, false, true),
+        case("aab", true, true, true),
+        case("abaab", false, false, false),
+        case("aabab", true, true, true),
+        case("aabaa", false, false, true)
+    )]
+    fn test_predicates(s: &str, lyndon: bool, necklace: bool, prenecklace: bool) {
+        assert_eq!(is_lyndon(s), lyndon);
+        assert_eq!(is_necklace(s), necklace);
+        assert_eq!(is_prenecklace(s), prenecklace);
+    }
+
+    /// Every word of length `n` over `abc`.
+    fn words(n: usize) -> Vec<String> {
+        (0..n).fold(vec![String::new()], |words, _| {
+            words
+                .iter()
+                .flat_map(|w| "abc".chars().map(move |c| format!("{}{}", w, c)))
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_against_enumeration() {
+        let alphabet = ['a', 'b', 'c'];
+        for n in 0..=6 {
+            let to_string = |w: &[char]| w.iter().collect::<String>();
+            let lyndon: Vec<String> = lyndon_words(&alphabet, n)
+                .map(|w| to_string(w.as_slice()))
+                .collect();
+            let necklaces: Vec<String> = necklaces(&alphabet, n)
+                .map(|w| to_string(w.as_slice()))
+                .collect();
+            let prenecklaces: Vec<String> =
+                prenecklaces(&alphabet, n).map(|w| to_string(&w)).collect();
+            for word in words(n) {
+                assert_eq!(is_lyndon(&word), lyndon.contains(&word), "{}", word);
+                assert_eq!(is_necklace(&word), necklaces.contains(&word), "{}", word);
+                assert_eq!(
+                    is_prenecklace(&word),
+                    prenecklaces.contains(&word),
+                    "{}",
+                    word
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_least_rotation_at_factor() {
+        // the least rotation always begins where some factor does
+        for n in 1..=7 {
+            for word in words(n) {
+                let chars: Vec<char> = word.chars().collect();
+                let offset = least_rotation(&chars);
+                let mut rotated = chars.clone();
+                rotated.rotate_left(offset);
+                let starts = duval(&chars).into_iter().map(|r| r.start);
+                assert!(
+                    starts.into_iter().any(|start| {
+                        let mut c = chars.clone();
+                        c.rotate_left(start);
+                        c == rotated
+                    }),
+                    "{}",
+                    word
+                );
+            }
+        }
+    }
+}
@@ -1,53 +1,293 @@
-use anyhow::Result;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
+use anyhow::{anyhow, bail, Result};
+use num_bigint::BigUint;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::hash::Hash;
+use std::io::{self, BufRead, Write};
 use std::path::PathBuf;
-use string_necklace::StringNecklace;
+use std::str::FromStr;
+use string_necklace::bwt;
+use string_necklace::count::{
+    bracelet_count, fixed_content_necklace_count, lyndon_count, necklace_count,
+};
+use string_necklace::de_bruijn::de_bruijn;
+use string_necklace::enumerate::{lyndon_words, necklaces, prenecklaces};
+use string_necklace::{rotation_between, NecklaceRef, StringBracelet};
 use structopt::StructOpt;
 
-pub fn matches_in<Iter, Str>(words: Iter) -> HashMap<StringNecklace, usize>
+/// Group the words which are equivalent under `key`, in the order they appear.
+pub fn matches_in<'a, Iter, Str, Key, F>(words: Iter, key: F) -> HashMap<Key, Vec<&'a str>>
 where
-    Iter: IntoIterator<Item = Str>,
-    Str: AsRef<str>,
+    Iter: IntoIterator<Item = &'a Str>,
+    Str: 'a + AsRef<str> + ?Sized,
+    Key: Hash + Eq,
+    F: Fn(&'a str) -> Key,
 {
-    let mut h = HashMap::new();
+    let mut h: HashMap<Key, Vec<&str>> = HashMap::new();
     for word in words.into_iter() {
-        let necklace = StringNecklace::new(word);
-        *h.entry(necklace).or_default() += 1;
+        let word = word.as_ref();
+        h.entry(key(word)).or_default().push(word);
     }
     h
 }
 
 #[derive(Debug, StructOpt)]
-#[structopt(
-    about = "Given a wordlist, get those items in the wordlist which have N matches as string necklaces."
-)]
-struct Opt {
-    /// Path to wordlist
-    #[structopt(parse(from_os_str))]
-    wordlist: PathBuf,
+#[structopt(about = "Compare strings as necklaces, and generate necklaces.")]
+enum Opt {
+    /// Given a wordlist, get those items in the wordlist which have N matches as string necklaces.
+    Matches {
+        /// Path to wordlist
+        #[structopt(parse(from_os_str))]
+        wordlist: PathBuf,
 
-    /// How many equivalents we should look for
-    n: usize,
+        /// How many equivalents we should look for
+        n: usize,
+
+        /// Match words as bracelets, which may also be turned over, so that "abc" matches "cba"
+        #[structopt(long)]
+        bracelet: bool,
+
+        /// Report how far the first word of each group rotates to give each of the others
+        #[structopt(long)]
+        shifts: bool,
+    },
+
+    /// List every necklace, Lyndon word, or prenecklace of length N over an alphabet.
+    Enumerate {
+        /// What to list: necklaces, lyndon, or prenecklaces
+        kind: Kind,
+
+        /// The length of each one
+        n: usize,
+
+        /// The symbols to use
+        #[structopt(long, default_value = "ab")]
+        alphabet: String,
+
+        /// Only count them
+        #[structopt(long)]
+        count: bool,
+    },
+
+    /// Count necklaces, Lyndon words, or bracelets, without listing them.
+    Count(CountOpt),
+
+    /// Compress or decompress a file with the Burrows–Wheeler transform.
+    Bwt(BwtOpt),
+
+    /// Print a de Bruijn sequence, which contains every word of length N once when read cyclically.
+    DeBruijn {
+        /// The length of the words it contains
+        n: usize,
+
+        /// The symbols to use
+        #[structopt(long, default_value = "ab")]
+        alphabet: String,
+
+        /// Repeat the start at the end, so that every word appears when it's read straight through
+        #[structopt(long)]
+        linear: bool,
+    },
 }
 
-fn main() -> Result<()> {
-    let Opt { wordlist, n } = Opt::from_args();
+#[derive(Debug, StructOpt)]
+enum CountOpt {
+    /// Count the necklaces of length N over K symbols
+    Necklaces { n: usize, k: usize },
+
+    /// Count the Lyndon words of length N over K symbols
+    Lyndon { n: usize, k: usize },
+
+    /// Count the bracelets of length N over K symbols
+    Bracelets { n: usize, k: usize },
+
+    /// Count the necklaces made of the same letters as WORD, e.g. 2 for "aabb"
+    Content { word: String },
+}
+
+#[derive(Debug, StructOpt)]
+enum BwtOpt {
+    /// Transform INPUT by sorting its rotations, then move symbols to the front and encode runs
+    Compress {
+        #[structopt(parse(from_os_str))]
+        input: PathBuf,
+
+        #[structopt(parse(from_os_str))]
+        output: PathBuf,
+
+        /// Use the bijective BWT, which rotates Lyndon factors instead of the whole input
+        #[structopt(long)]
+        bijective: bool,
+    },
+
+    /// Restore a file which `bwt compress` produced
+    Decompress {
+        #[structopt(parse(from_os_str))]
+        input: PathBuf,
+
+        #[structopt(parse(from_os_str))]
+        output: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Necklaces,
+    Lyndon,
+    Prenecklaces,
+}
+
+impl FromStr for Kind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Kind> {
+        match s {
+            "necklaces" => Ok(Kind::Necklaces),
+            "lyndon" => Ok(Kind::Lyndon),
+            "prenecklaces" => Ok(Kind::Prenecklaces),
+            _ => bail!("expected necklaces, lyndon, or prenecklaces; got {:?}", s),
+        }
+    }
+}
+
+/// How `from` turns into `to`: rotated left by some shifts, perhaps after being reversed.
+fn relation(from: &str, to: &str) -> String {
+    let to: Vec<char> = to.chars().collect();
+    let mut from: Vec<char> = from.chars().collect();
+    let mut how = "rotated";
+    let shifts = rotation_between(&from, &to).or_else(|| {
+        from.reverse();
+        how = "reversed and rotated";
+        rotation_between(&from, &to)
+    });
+    match shifts {
+        Some(shifts) => {
+            let shifts: Vec<String> = shifts.iter().map(|k| k.to_string()).collect();
+            format!("{} left by {}", how, shifts.join(" or "))
+        }
+        None => "unrelated".into(),
+    }
+}
+
+fn print_groups<Key>(groups: HashMap<Key, Vec<&str>>, n: usize, shifts: bool) {
+    for words in groups.values().filter(|words| words.len() == n) {
+        for (i, word) in words.iter().enumerate() {
+            if shifts && i > 0 {
+                println!("{}: {} {}", word, words[0], relation(words[0], word));
+            } else {
+                println!("{}", word);
+            }
+        }
+        println!();
+    }
+}
+
+fn matches(wordlist: PathBuf, n: usize, bracelet: bool, shifts: bool) -> Result<()> {
     let file = File::open(wordlist)?;
     let words: Vec<String> = io::BufReader::new(file)
         .lines()
         .collect::<std::result::Result<Vec<String>, _>>()?;
 
-    let mut match_map = matches_in(&words);
-    match_map.retain(|_, count| *count == n);
-    for sn in match_map.keys() {
-        for word in &words {
-            if &StringNecklace::new(word) == sn {
-                println!("{}", word);
+    if bracelet {
+        print_groups(matches_in(&words, StringBracelet::new), n, shifts);
+    } else {
+        print_groups(matches_in(&words, NecklaceRef::from), n, shifts);
+    }
+    Ok(())
+}
+
+fn enumerate(kind: Kind, n: usize, alphabet: &str, count: bool) {
+    let alphabet: Vec<char> = alphabet.chars().collect();
+    let words: Box<dyn Iterator<Item = String>> = match kind {
+        Kind::Necklaces => Box::new(necklaces(&alphabet, n).map(|n| n.to_string())),
+        Kind::Lyndon => Box::new(lyndon_words(&alphabet, n).map(|n| n.to_string())),
+        Kind::Prenecklaces => Box::new(prenecklaces(&alphabet, n).map(|p| p.into_iter().collect())),
+    };
+    if count {
+        println!("{}", words.count());
+    } else {
+        let stdout = io::stdout();
+        let mut out = io::BufWriter::new(stdout.lock());
+        for word in words {
+            if writeln!(out, "{}", word).is_err() {
+                return;
             }
         }
-        println!();
+    }
+}
+
+fn count(opt: CountOpt) -> BigUint {
+    match opt {
+        CountOpt::Necklaces { n, k } => necklace_count(k, n),
+        CountOpt::Lyndon { n, k } => lyndon_count(k, n),
+        CountOpt::Bracelets { n, k } => bracelet_count(k, n),
+        CountOpt::Content { word } => {
+            let mut letters: BTreeMap<char, usize> = BTreeMap::new();
+            for c in word.chars() {
+                *letters.entry(c).or_default() += 1;
+            }
+            fixed_content_necklace_count(&letters.values().copied().collect::<Vec<_>>())
+        }
+    }
+}
+
+fn bwt(opt: BwtOpt) -> Result<()> {
+    match opt {
+        BwtOpt::Compress {
+            input,
+            output,
+            bijective,
+        } => {
+            let data = fs::read(input)?;
+            let compressed = bwt::compress(&data, bijective);
+            eprintln!("{} bytes -> {} bytes", data.len(), compressed.len());
+            fs::write(output, compressed)?;
+        }
+        BwtOpt::Decompress { input, output } => {
+            let data = fs::read(&input)?;
+            let decompressed = bwt::decompress(&data)
+                .ok_or_else(|| anyhow!("{} is not BWT-compressed", input.display()))?;
+            fs::write(output, decompressed)?;
+        }
     }
     Ok(())
 }
+
+fn main() -> Result<()> {
+    match Opt::from_args() {
+        Opt::Matches {
+            wordlist,
+            n,
+            bracelet,
+            shifts,
+        } => matches(wordlist, n, bracelet, shifts),
+        Opt::Enumerate {
+            kind,
+            n,
+            alphabet,
+            count,
+        } => {
+            enumerate(kind, n, &alphabet, count);
+            Ok(())
+        }
+        Opt::DeBruijn {
+            n,
+            alphabet,
+            linear,
+        } => {
+            let alphabet: Vec<char> = alphabet.chars().collect();
+            let mut seq = de_bruijn(&alphabet, n);
+            if linear {
+                let wrap = seq.len().min(n.saturating_sub(1));
+                seq.extend_from_within(..wrap);
+            }
+            println!("{}", seq.into_iter().collect::<String>());
+            Ok(())
+        }
+        Opt::Bwt(opt) => bwt(opt),
+        Opt::Count(opt) => {
+            println!("{}", count(opt));
+            Ok(())
+        }
+    }
+}